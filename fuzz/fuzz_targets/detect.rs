@@ -0,0 +1,9 @@
+//! Detects the format of arbitrary contents with [`unrar::detect`].
+//! See `fuzz/README.md` for how to seed the corpus.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = unrar::detect(data);
+});
@@ -0,0 +1,24 @@
+//! Lists arbitrary archive contents with [`Archive::open_for_listing`], which only parses
+//! headers, and once more with the service headers, whose headers are walked in Rust rather
+//! than by unrar. See `fuzz/README.md` for how to seed the corpus.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use std::io::Write;
+use unrar::{Archive, ListOptions};
+
+fuzz_target!(|data: &[u8]| {
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    file.write_all(data).unwrap();
+    let options = ListOptions {
+        include_services: true,
+    };
+    let _ = Archive::new(file.path()).list(options);
+    let Ok(archive) = Archive::new(file.path()).open_for_listing() else {
+        return;
+    };
+    // the iterator ends on its own after an error in the archive, there is no need to stop
+    for entry in archive {
+        let _ = entry.map(|entry| entry.is_directory());
+    }
+});
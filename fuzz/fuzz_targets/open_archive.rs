@@ -0,0 +1,23 @@
+//! Processes arbitrary archive contents and reads every entry, which drives the
+//! decompression and decryption code. See `fuzz/README.md` for how to seed the corpus.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use std::io::Write;
+use unrar::Archive;
+
+fuzz_target!(|data: &[u8]| {
+    // the unrar library only opens archives from the file system
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    file.write_all(data).unwrap();
+    let Ok(mut archive) = Archive::new(file.path()).open_for_processing() else {
+        return;
+    };
+    // errors are fine, panics and crashes are not
+    while let Ok(Some(header)) = archive.read_header() {
+        match header.read() {
+            Ok((_, next)) => archive = next,
+            Err(_) => break,
+        }
+    }
+});
@@ -0,0 +1,33 @@
+//! Exercises the volume change and data callbacks with encrypted, multipart archives.
+//!
+//! The last two bytes of the input select where the archive is cut into two volumes and
+//! which of the passwords used in `data/` is tried, the rest is the archive itself. This
+//! way, the files in `data/` remain sensible seeds. See `fuzz/README.md`.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use unrar::Archive;
+
+const PASSWORDS: [&[u8]; 2] = [b"unrar", b"password"];
+
+fuzz_target!(|data: &[u8]| {
+    let Some((archive, [a, b])) = data.split_last_chunk::<2>() else {
+        return;
+    };
+    let selector = u16::from_le_bytes([*a, *b]) as usize;
+    let (first, second) = archive.split_at(selector % (archive.len() + 1));
+    let dir = tempfile::tempdir().unwrap();
+    let part1 = dir.path().join("fuzz.part1.rar");
+    std::fs::write(&part1, first).unwrap();
+    std::fs::write(dir.path().join("fuzz.part2.rar"), second).unwrap();
+    let password = PASSWORDS[selector % PASSWORDS.len()];
+    let Ok(mut archive) = Archive::with_password(&part1, password).open_for_processing() else {
+        return;
+    };
+    while let Ok(Some(header)) = archive.read_header() {
+        match header.read() {
+            Ok((_, next)) => archive = next,
+            Err(_) => break,
+        }
+    }
+});
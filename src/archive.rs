@@ -1,8 +1,20 @@
 use crate::error::*;
-use crate::open_archive::{CursorBeforeHeader, List, ListSplit, OpenArchive, OpenMode, Process};
+use crate::index::ArchiveIndex;
+use crate::limits::{Budget, ExtractLimits};
+use crate::memory::MemoryTree;
+use crate::native;
+use crate::open_archive::{
+    CursorBeforeFile, CursorBeforeHeader, FileHeader, List, ListSplit, OpenArchive, OpenMode,
+    Process,
+};
+use crate::parallel;
+use crate::password::{self, SecretPassword};
+use crate::pathed;
+use crate::report::{self, TestReport};
+use crate::services::{self, ListOptions};
+use crate::sink::{self, MemorySink, Sink};
 use regex::Regex;
 use std::borrow::Cow;
-use std::iter::repeat;
 use std::path::{Path, PathBuf};
 use std::sync::OnceLock;
 
@@ -20,26 +32,42 @@ fn extension() -> &'static Regex {
 ///
 /// This struct provides two major classes of methods:
 ///    1. methods that do not touch the FS. These are opinionated utility methods
-///         that are based on RAR path conventions out in the wild. Most commonly, multipart
-///         files usually have extensions such as `.part08.rar` or `.r08.rar`. Since extracting
-///         must start at the first part, it may be helpful to figure that out using, for instance,
-///         [`archive.as_first_part()`](Archive::as_first_part)
+///       that are based on RAR path conventions out in the wild. Most commonly, multipart
+///       files usually have extensions such as `.part08.rar` or `.r08.rar`. Since extracting
+///       must start at the first part, it may be helpful to figure that out using, for instance,
+///       [`archive.as_first_part()`](Archive::as_first_part)
 ///    2. methods that open the underlying path in the specified mode
-///         (possible modes are [`List`], [`ListSplit`] and [`Process`]).
-///         These methods have the word `open` in them, are fallible operations,
-///         return [`OpenArchive`](struct.OpenArchive.html) inside a `Result` and are as follows:
-///         - [`open_for_listing`](Archive::open_for_listing) and
-///             [`open_for_listing_split`](Archive::open_for_listing_split): list the archive
-///             entries (skipping over content/payload)
-///         - [`open_for_processing`](Archive::open_for_processing): process archive entries
-///             as well as content/payload
-///         - [`break_open`](Archive::break_open): read archive even if an error is returned,
-///             if possible. The [`OpenMode`](open_archive/struct.OpenMode.html) must be provided
-///             explicitly.
+///       (possible modes are [`List`], [`ListSplit`] and [`Process`]).
+///       These methods have the word `open` in them, are fallible operations,
+///       return [`OpenArchive`](struct.OpenArchive.html) inside a `Result` and are as follows:
+///       - [`open_for_listing`](Archive::open_for_listing) and
+///         [`open_for_listing_split`](Archive::open_for_listing_split): list the archive
+///         entries (skipping over content/payload)
+///       - [`open_for_processing`](Archive::open_for_processing): process archive entries
+///         as well as content/payload
+///       - [`break_open`](Archive::break_open): read archive even if an error is returned,
+///         if possible. The [`OpenMode`](open_archive/struct.OpenMode.html) must be provided
+///         explicitly.
 pub struct Archive<'a> {
     filename: Cow<'a, Path>,
-    password: Option<&'a [u8]>,
+    password: Option<SecretPassword>,
     comments: Option<&'a mut Vec<u8>>,
+    limits: ExtractLimits,
+    #[cfg(feature = "encoding")]
+    encoding: Option<&'static encoding_rs::Encoding>,
+}
+
+/// How an archive is encrypted, which tells whether it can be opened without a password,
+/// see [`Archive::probe`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OpenOutcome {
+    /// nothing is encrypted
+    NotEncrypted,
+    /// the headers are not encrypted, so the entries can be listed without a password, but
+    /// the data of some of them is. Extracting those needs the password.
+    ContentsEncrypted,
+    /// the headers are encrypted, so not even listing the entries works without the password
+    NeedsPassword,
 }
 
 pub type Glob = PathBuf;
@@ -54,19 +82,38 @@ impl<'a> Archive<'a> {
             filename: Cow::Borrowed(file.as_ref()),
             password: None,
             comments: None,
+            limits: ExtractLimits::new(),
+            #[cfg(feature = "encoding")]
+            encoding: None,
         }
     }
 
     /// Creates an `Archive` object to operate on a password encrypted RAR archive.
-    pub fn with_password<F, Pw>(file: &'a F, password: &'a Pw) -> Self
+    ///
+    /// The password is taken as UTF-8, see [`SecretPassword::from_bytes`] for bytes that are
+    /// not. It is copied into a [`SecretPassword`] right away, wiping the copy is up to the
+    /// caller.
+    pub fn with_password<F, Pw>(file: &'a F, password: &Pw) -> Self
     where
         F: AsRef<Path> + ?Sized,
         Pw: AsRef<[u8]> + ?Sized,
+    {
+        Self::with_secret_password(file, SecretPassword::from_bytes(password.as_ref()))
+    }
+
+    /// Creates an `Archive` object to operate on a password encrypted RAR archive, taking
+    /// over a [`SecretPassword`].
+    pub fn with_secret_password<F>(file: &'a F, password: SecretPassword) -> Self
+    where
+        F: AsRef<Path> + ?Sized,
     {
         Archive {
             filename: Cow::Borrowed(file.as_ref()),
-            password: Some(password.as_ref()),
+            password: Some(password),
             comments: None,
+            limits: ExtractLimits::new(),
+            #[cfg(feature = "encoding")]
+            encoding: None,
         }
     }
 
@@ -75,6 +122,65 @@ impl<'a> Archive<'a> {
         &self.filename
     }
 
+    /// Sets the [`ExtractLimits`] enforced on every archive opened from this one.
+    pub fn with_limits(mut self, limits: ExtractLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Decodes the names of entries of RAR 1.5 to 4.x archives opened from this one from
+    /// `encoding`, for archives created on systems whose names were not Unicode, e.g. in
+    /// [`IBM866`](encoding_rs::IBM866) or [`SHIFT_JIS`](encoding_rs::SHIFT_JIS).
+    ///
+    /// RAR 3.x and later also store names as Unicode if the code page cannot represent them.
+    /// unrar hands those out as UTF-8 on Unix, which is kept as is, as are names in
+    /// RAR 5.0 archives. The bytes unrar hands out, which are the stored ones on Unix other
+    /// than macOS, are available from [`FileHeader::raw_filename`] regardless of the
+    /// encoding.
+    ///
+    /// This only changes the names reported in [`FileHeader::filename`]: unrar extracts
+    /// entries under the names it decoded itself, so use
+    /// [`extract_to`](OpenArchive::extract_to) to extract them under the decoded ones.
+    ///
+    /// Only encodings of the WHATWG Encoding Standard are available, which lacks DOS code
+    /// pages like CP437.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use unrar::Archive;
+    ///
+    /// let archive = Archive::new("old.rar").with_filename_encoding(unrar::encoding_rs::IBM866);
+    /// for entry in archive.open_for_listing().unwrap() {
+    ///     println!("{}", entry.unwrap().filename.display());
+    /// }
+    /// ```
+    #[cfg(feature = "encoding")]
+    pub fn with_filename_encoding(mut self, encoding: &'static encoding_rs::Encoding) -> Self {
+        self.encoding = Some(encoding);
+        self
+    }
+
+    /// hands the [filename encoding](Archive::with_filename_encoding), if any, on to `archive`
+    fn decoding<M: OpenMode>(
+        &self,
+        archive: OpenArchive<M, CursorBeforeHeader>,
+    ) -> OpenArchive<M, CursorBeforeHeader> {
+        #[cfg(feature = "encoding")]
+        let archive = archive.with_filename_encoding(self.encoding);
+        archive
+    }
+
+    #[cfg(all(feature = "fuse", target_os = "linux"))]
+    pub(crate) fn password(&self) -> Option<&SecretPassword> {
+        self.password.as_ref()
+    }
+
+    #[cfg(all(feature = "fuse", target_os = "linux"))]
+    pub(crate) fn limits(&self) -> ExtractLimits {
+        self.limits
+    }
+
     /// Set the comment buffer of the underlying archive.
     /// Note: Comments are not supported yet so this method will have no effect.
     pub fn set_comments(&mut self, comments: &'a mut Vec<u8>) {
@@ -84,6 +190,7 @@ impl<'a> Archive<'a> {
     /// Returns `true` if the filename matches a RAR archive.
     ///
     /// This method does not make any FS operations and operates purely on strings.
+    /// To inspect the contents of the file instead, see [`detect`](crate::detect).
     pub fn is_archive(&self) -> bool {
         is_archive(&self.filename)
     }
@@ -91,6 +198,7 @@ impl<'a> Archive<'a> {
     /// Returns `true` if the filename matches a part of a multipart collection, `false` otherwise
     ///
     /// This method does not make any FS operations and operates purely on strings.
+    /// To inspect the contents of the file instead, see [`detect`](crate::detect).
     pub fn is_multipart(&self) -> bool {
         is_multipart(&self.filename)
     }
@@ -124,11 +232,7 @@ impl<'a> Archive<'a> {
             .and_then(|full_ext| {
                 multipart_extension().captures(&full_ext).map(|captures| {
                     let mut replacement = String::from(captures.get(1).unwrap().as_str());
-                    replacement.push_str(
-                        &repeat("?")
-                            .take(captures.get(2).unwrap().as_str().len())
-                            .collect::<String>(),
-                    );
+                    replacement.push_str(&"?".repeat(captures.get(2).unwrap().as_str().len()));
                     replacement.push_str(captures.get(3).unwrap().as_str());
                     full_ext.replace(captures.get(0).unwrap().as_str(), &replacement)
                 })
@@ -312,8 +416,9 @@ impl<'a> Archive<'a> {
     /// assert_eq!(archive.filename(), PathBuf::from("path/some.001.rar"));
     /// ```
     pub fn as_first_part(mut self) -> Self {
-        self.first_part_option()
-            .map(|fp| self.filename = Cow::Owned(fp));
+        if let Some(fp) = self.first_part_option() {
+            self.filename = Cow::Owned(fp);
+        }
         self
     }
 
@@ -323,20 +428,27 @@ impl<'a> Archive<'a> {
     ///
     /// See also: [`Process`]
     ///
-    /// # Panics
+    /// # Errors
     ///
-    /// Panics if `self.filename` contains nul values.
+    /// Returns an error with [`Code::Nul`](crate::error::Code::Nul) if `self.filename` or the
+    /// password contain nul values.
     pub fn open_for_processing(self) -> UnrarResult<OpenArchive<Process, CursorBeforeHeader>> {
         self.open(None)
     }
 
     /// Opens the underlying archive for listing its entries, i.e. the payloads are skipped automatically.
     ///
+    /// If the headers of the archive are encrypted, listing needs the password: without one,
+    /// reading the first entry fails with
+    /// [`Code::MissingPassword`](crate::error::Code::MissingPassword). Use
+    /// [`probe`](Archive::probe) to find out beforehand.
+    ///
     /// See also: [`List`]
     ///
-    /// # Panics
+    /// # Errors
     ///
-    /// Panics if `self.filename` contains nul values.
+    /// Returns an error with [`Code::Nul`](crate::error::Code::Nul) if `self.filename` or the
+    /// password contain nul values.
     pub fn open_for_listing(self) -> UnrarResult<OpenArchive<List, CursorBeforeHeader>> {
         self.open(None)
     }
@@ -348,24 +460,470 @@ impl<'a> Archive<'a> {
     ///
     /// See also: [`ListSplit`]
     ///
-    /// # Panics
+    /// # Errors
     ///
-    /// Panics if `self.filename` contains nul values.
-
+    /// Returns an error with [`Code::Nul`](crate::error::Code::Nul) if `self.filename` or the
+    /// password contain nul values.
     pub fn open_for_listing_split(self) -> UnrarResult<OpenArchive<ListSplit, CursorBeforeHeader>> {
         self.open(None)
     }
 
-    /// Opens the underlying archive with the provided parameters.
+    /// Lists the underlying archive once and returns an [`ArchiveIndex`] of its entries,
+    /// which can be used to [`open_entry`](Archive::open_entry) by name later on.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error with [`Code::Nul`](crate::error::Code::Nul) if `self.filename` or the
+    /// password contain nul values.
+    pub fn index(&self) -> UnrarResult<ArchiveIndex> {
+        let budget = Budget::new(self.limits);
+        ArchiveIndex::new(self.decoding(OpenArchive::new(
+            &self.filename,
+            self.password.as_ref(),
+            budget,
+            None,
+        )?))
+    }
+
+    /// Lists the entries of the underlying archive like
+    /// [`open_for_listing`](Archive::open_for_listing), see [`ListOptions`] for how.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use unrar::{Archive, ListOptions};
+    ///
+    /// let options = ListOptions { include_services: true };
+    /// let entries = Archive::new("data/multi.rar").list(options).unwrap();
+    /// assert_eq!(entries.len(), 5);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error with [`Code::Nul`](crate::error::Code::Nul) if `self.filename` or the
+    /// password contain nul values.
+    pub fn list(self, options: ListOptions) -> UnrarResult<Vec<FileHeader>> {
+        let entries = self.list_services(options)?;
+        Ok(entries.into_iter().map(|(entry, _)| entry).collect())
+    }
+
+    /// lists the entries like [`list`](Archive::list) along with where the data of the
+    /// service headers is
+    fn list_services(
+        self,
+        options: ListOptions,
+    ) -> UnrarResult<Vec<(FileHeader, Option<services::Data>)>> {
+        let filename = self.filename.to_path_buf();
+        let entries = self.open_for_listing()?.collect::<UnrarResult<Vec<_>>>()?;
+        Ok(match options.include_services {
+            true => services::insert(entries, services::read(&filename, next_part)),
+            false => entries.into_iter().map(|entry| (entry, None)).collect(),
+        })
+    }
+
+    /// Writes the NTFS alternate data streams of the underlying archive to the files
+    /// extracted from it to `base`, as extended attributes or as sidecar files, see
+    /// [`StreamTarget`](crate::StreamTarget).
+    ///
+    /// unrar only restores streams on Windows, and only decompresses their data there, so
+    /// only the data of streams stored without compression can be written. Returns the
+    /// streams that were skipped because their data is compressed, encrypted or split
+    /// across volumes.
+    ///
+    /// Streams are only written below `base`, like [`FsSink`](crate::FsSink) writes entries:
+    /// files whose path is absolute, leads out of `base` or runs through a symbolic link
+    /// are rejected, and so are stream names holding separators, `..` or nul values.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use unrar::{Archive, StreamTarget};
+    ///
+    /// Archive::new("archive.rar").extract_all_parallel("dest", 4).unwrap();
+    /// let skipped = Archive::new("archive.rar")
+    ///     .extract_streams("dest", StreamTarget::Xattr)
+    ///     .unwrap();
+    /// for stream in skipped {
+    ///     eprintln!("cannot restore {}", stream.filename.display());
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error with [`Code::BadData`](crate::error::Code::BadData) if the CRC of a
+    /// stream does not match, or one for the failed file system operation if a stream
+    /// could not be written, e.g. because the file it belongs to was not extracted. Streams
+    /// rejected for their path fail with an [`io_error`](crate::error::UnrarError::io_error)
+    /// of the kind [`InvalidInput`](std::io::ErrorKind::InvalidInput).
+    #[cfg(target_os = "linux")]
+    pub fn extract_streams<P: AsRef<Path>>(
+        self,
+        base: P,
+        target: crate::StreamTarget,
+    ) -> UnrarResult<Vec<FileHeader>> {
+        let options = ListOptions {
+            include_services: true,
+        };
+        let mut skipped = Vec::new();
+        for (entry, data) in self.list_services(options)? {
+            let Some(data) = data else {
+                continue;
+            };
+            if !services::write(base.as_ref(), &entry, &data, target)? {
+                skipped.push(entry);
+            }
+        }
+        Ok(skipped)
+    }
+
+    /// Opens the underlying archive for processing and advances it to the entry called `name`,
+    /// returning `None` if there is no such entry.
+    ///
+    /// For non-solid archives, the offset stored in `index` is used to go to the entry by
+    /// counting the headers in between, whose payloads are skipped without decompressing
+    /// them. The unrar library cannot seek to a header, so the headers are still read, and
+    /// the time this takes grows with the number of entries before `name`. Entries of solid
+    /// archives can only be reached by decompressing everything before them, so for those,
+    /// the archive is scanned sequentially for `name` instead. Either way, the entry found
+    /// has to match the one in `index`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use unrar::Archive;
+    ///
+    /// let index = Archive::new("data/multi.rar").index().unwrap();
+    /// let entry = Archive::new("data/multi.rar").open_entry(&index, "VERSION").unwrap().unwrap();
+    /// assert_eq!(entry.read().unwrap().0, b"unrar-0.5.8");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error with [`Code::Nul`](crate::error::Code::Nul) if `self.filename` or the
+    /// password contain nul values, or with [`Code::BadArchive`](crate::error::Code::BadArchive)
+    /// if `index` does not fit the archive, e.g. because it was built from another one.
+    pub fn open_entry<P: AsRef<Path>>(
+        self,
+        index: &ArchiveIndex,
+        name: P,
+    ) -> UnrarResult<Option<OpenArchive<Process, CursorBeforeFile>>> {
+        index.seek(self.open_for_processing()?, name.as_ref())
+    }
+
+    /// Extracts all entries of the underlying archive into memory, see [`MemoryTree`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use unrar::Archive;
+    ///
+    /// let tree = Archive::new("data/version.rar").extract_all_to_memory().unwrap();
+    /// assert_eq!(tree.read("VERSION"), Some(&b"unrar-0.4.0"[..]));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error with [`Code::Nul`](crate::error::Code::Nul) if `self.filename` or the
+    /// password contain nul values.
+    pub fn extract_all_to_memory(self) -> UnrarResult<MemoryTree> {
+        let mut sink = MemorySink::new();
+        self.extract_all(&mut sink)?;
+        Ok(sink.into_tree())
+    }
+
+    /// Extracts all entries of the underlying archive into `sink`, see [`Sink`] for how.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use unrar::{Archive, FsSink};
+    ///
+    /// let dest = tempfile::tempdir().unwrap();
+    /// let mut sink = FsSink::new(dest.path());
+    /// Archive::new("data/multi.rar").extract_all(&mut sink).unwrap();
+    /// assert!(dest.path().join("src/lib.rs").is_file());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error with [`Code::Nul`](crate::error::Code::Nul) if `self.filename` or the
+    /// password contain nul values. Errors returned by `sink` are reported as
+    /// [`Code::ECreate`](crate::error::Code::ECreate) for creating entries,
+    /// [`Code::EWrite`](crate::error::Code::EWrite) for writing data and
+    /// [`Code::EClose`](crate::error::Code::EClose) for finishing files, with the error
+    /// itself as the [`io_error`](UnrarError::io_error).
+    pub fn extract_all<S: Sink>(self, sink: &mut S) -> UnrarResult<()> {
+        sink::extract(self.open_for_processing()?, sink)
+    }
+
+    /// Finds out how the underlying archive is encrypted without a password, see
+    /// [`OpenOutcome`].
+    ///
+    /// Archives whose headers are encrypted tell so right away. Otherwise, the entries are
+    /// listed until the first encrypted one, so archives with no encrypted entries are
+    /// listed in full. The password of this `Archive` is not used.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use unrar::{Archive, OpenOutcome};
+    ///
+    /// let outcome = Archive::new("data/comment-hpw-password.rar").probe().unwrap();
+    /// assert_eq!(outcome, OpenOutcome::NeedsPassword);
+    /// let outcome = Archive::new("data/crypted.rar").probe().unwrap();
+    /// assert_eq!(outcome, OpenOutcome::ContentsEncrypted);
+    /// let outcome = Archive::new("data/version.rar").probe().unwrap();
+    /// assert_eq!(outcome, OpenOutcome::NotEncrypted);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the archive cannot be opened or listed, e.g. with
+    /// [`Code::Nul`](crate::error::Code::Nul) if `self.filename` contains nul values.
+    pub fn probe(&self) -> UnrarResult<OpenOutcome> {
+        let archive =
+            OpenArchive::<List, _>::new(&self.filename, None, Budget::new(self.limits), None);
+        let archive = match archive {
+            Ok(archive) if archive.has_encrypted_headers() => {
+                return Ok(OpenOutcome::NeedsPassword)
+            }
+            Ok(archive) => archive,
+            Err(e) if e.code == Code::MissingPassword => return Ok(OpenOutcome::NeedsPassword),
+            Err(e) => return Err(e),
+        };
+        for entry in self.decoding(archive) {
+            if entry?.is_encrypted() {
+                return Ok(OpenOutcome::ContentsEncrypted);
+            }
+        }
+        Ok(OpenOutcome::NotEncrypted)
+    }
+
+    /// Checks whether `password` is the one the underlying archive is encrypted with,
+    /// without extracting anything.
+    ///
+    /// The password is checked against the first encrypted entry, or against the headers if
+    /// those are encrypted. Entries of RAR 5.0 archives usually store a check value of the
+    /// password, so testing stops as soon as the first data has been decrypted. Older
+    /// archives and entries without a check value only tell by the CRC, so the first
+    /// encrypted entry is decompressed in full and damaged data counts as a wrong password.
+    ///
+    /// Archives without encrypted entries accept any password. The password of this
+    /// `Archive` is not used.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use unrar::Archive;
+    ///
+    /// let archive = Archive::new("data/unicode-password.rar");
+    /// assert!(archive.check_password("pässwörd✓").unwrap());
+    /// assert!(!archive.check_password("password").unwrap());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the archive cannot be read, e.g. with
+    /// [`Code::Nul`](crate::error::Code::Nul) if `self.filename` or the password contain nul
+    /// values.
+    pub fn check_password(&self, password: impl Into<SecretPassword>) -> UnrarResult<bool> {
+        let checked = services::password_checks(&self.filename, next_part);
+        self.check_secret_password(&password.into(), &checked)
+    }
+
+    /// see [`OpenArchive::check_password`] for `checked`
+    pub(crate) fn check_secret_password(
+        &self,
+        password: &SecretPassword,
+        checked: &[FileHeader],
+    ) -> UnrarResult<bool> {
+        let archive = OpenArchive::<Process, _>::new(
+            &self.filename,
+            Some(password),
+            Budget::new(self.limits),
+            None,
+        );
+        password::check(archive.map(|archive| self.decoding(archive)), checked)
+    }
+
+    /// Finds the password the underlying archive is encrypted with among `candidates`,
+    /// returning the offset of the first one that fits, or `None` if none does.
+    ///
+    /// Every candidate is checked as by [`check_password`](Self::check_password), on a
+    /// handle of its own: unrar only asks for the password once per handle, and archives
+    /// with [encrypted headers](crate::OpenArchive::has_encrypted_headers) need it to be
+    /// opened at all. A candidate is rejected as soon as opening the archive, reading its
+    /// headers or decrypting its first encrypted entry fails for the password. The password
+    /// of this `Archive` is not used.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use unrar::Archive;
+    ///
+    /// let candidates = ["hunter2", "password", "pässwörd✓"];
+    /// let archive = Archive::new("data/unicode-password.rar");
+    /// assert_eq!(archive.try_passwords(candidates).unwrap(), Some(2));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Stops at the first candidate that fails for another reason than being the wrong
+    /// password, e.g. because the archive cannot be read, and returns its error. Candidates
+    /// containing nul values fail with [`Code::Nul`](crate::error::Code::Nul).
+    pub fn try_passwords<I>(&self, candidates: I) -> UnrarResult<Option<usize>>
+    where
+        I: IntoIterator,
+        I::Item: Into<SecretPassword>,
+    {
+        let checked = services::password_checks(&self.filename, next_part);
+        for (offset, candidate) in candidates.into_iter().enumerate() {
+            if self.check_secret_password(&candidate.into(), &checked)? {
+                return Ok(Some(offset));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Like [`try_passwords`](Self::try_passwords), but checks the candidates on `threads`
+    /// worker threads. Passing `0` threads is treated like passing `1`.
+    ///
+    /// The workers take the candidates off the iterator one at a time, so it is fine for it
+    /// to be long or even endless as long as one candidate fits. The offset returned is
+    /// still that of the first candidate that fits, and a candidate only fails the search
+    /// if none before it fits.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use unrar::Archive;
     ///
-    /// # Panics
+    /// let candidates = (0..100).map(|n| format!("guess{n}")).chain(["unrar".into()]);
+    /// let archive = Archive::new("data/crypted.rar");
+    /// assert_eq!(archive.try_passwords_parallel(candidates, 4).unwrap(), Some(100));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// See [`try_passwords`](Self::try_passwords).
+    pub fn try_passwords_parallel<I>(
+        &self,
+        candidates: I,
+        threads: usize,
+    ) -> UnrarResult<Option<usize>>
+    where
+        I: IntoIterator,
+        I::IntoIter: Send,
+        I::Item: Into<SecretPassword>,
+    {
+        let checked = services::password_checks(&self.filename, next_part);
+        parallel::try_passwords(self, &checked, candidates.into_iter(), threads.max(1))
+    }
+
+    /// Tests all entries of the underlying archive, going on past damaged ones, see
+    /// [`TestReport`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the archive cannot be opened, e.g. with [`Code::Nul`] if
+    /// `self.filename` or the password contain nul values. Errors of the entries are part of
+    /// the report.
+    ///
+    /// [`Code::Nul`]: crate::error::Code::Nul
+    pub fn test_all(self) -> UnrarResult<TestReport> {
+        Ok(report::run(self.open_for_processing()?, None))
+    }
+
+    /// Extracts all entries of the underlying archive into `dest` like
+    /// [`test_all`](Archive::test_all) tests them, keeping what could be extracted of damaged
+    /// files instead of deleting them, e.g. to recover as much as possible.
+    ///
+    /// Damaged files are kept as far as unrar could decompress them and may hold garbage.
+    /// They are reported as failed along with the others.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use unrar::Archive;
+    ///
+    /// let dest = tempfile::tempdir().unwrap();
+    /// let report = Archive::new("data/bad-crc.rar")
+    ///     .extract_all_keep_broken(dest.path())
+    ///     .unwrap();
+    /// assert_eq!(report.failed().count(), 1);
+    /// assert!(dest.path().join("bad.txt").exists());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the archive cannot be opened, e.g. with [`Code::Nul`] if
+    /// `self.filename`, the password or `dest` contain nul values. Errors of the entries are
+    /// part of the report.
+    ///
+    /// [`Code::Nul`]: crate::error::Code::Nul
+    pub fn extract_all_keep_broken<P: AsRef<Path>>(self, dest: P) -> UnrarResult<TestReport> {
+        let dest = dest.as_ref();
+        pathed::construct(dest).map_err(|e| UnrarError::nul(e, When::Process))?;
+        let archive = OpenArchive::with_flags(
+            &self.filename,
+            self.password.as_ref(),
+            Budget::new(self.limits),
+            native::ROADOF_KEEPBROKEN,
+            None,
+        )?;
+        Ok(report::run(self.decoding(archive), Some(dest)))
+    }
+
+    /// Extracts all entries of the underlying archive into `dest`, dividing them among
+    /// `threads` worker threads.
+    ///
+    /// Directories are created up front, after which each worker opens its own handle on the
+    /// archive and extracts a contiguous range of the files while skipping over the entries
+    /// before it. This only pays off for non-solid archives: entries of solid archives depend
+    /// on all entries before them, so those are always extracted by a single thread. Passing
+    /// `0` threads is treated like passing `1`.
+    ///
+    /// If extracting any entry fails, the error of the entry that comes first in the archive
+    /// is returned. Entries handled by other workers may have been extracted regardless.
     ///
-    /// Panics if `path` contains nul values.
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use unrar::Archive;
+    /// let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    /// Archive::new("large.rar").extract_all_parallel("out", threads).unwrap();
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error with [`Code::Nul`](crate::error::Code::Nul) if `self.filename`, the
+    /// password or `dest` contain nul values.
+    pub fn extract_all_parallel<P: AsRef<Path>>(self, dest: P, threads: usize) -> UnrarResult<()> {
+        parallel::extract_all(
+            &self.filename,
+            self.password.as_ref(),
+            &Budget::new(self.limits),
+            dest.as_ref(),
+            threads.max(1),
+        )
+    }
+
+    /// Opens the underlying archive with the provided parameters.
     fn open<M: OpenMode>(
         self,
         recover: Option<&mut Option<OpenArchive<M, CursorBeforeHeader>>>,
     ) -> UnrarResult<OpenArchive<M, CursorBeforeHeader>> {
-        OpenArchive::new(&self.filename, self.password, recover)
+        let mut recovered = None;
+        let result = OpenArchive::new(
+            &self.filename,
+            self.password.as_ref(),
+            Budget::new(self.limits),
+            recover.is_some().then_some(&mut recovered),
+        );
+        if let (Some(recover), Some(archive)) = (recover, recovered) {
+            *recover = Some(self.decoding(archive));
+        }
+        result.map(|archive| self.decoding(archive))
     }
 
     /// Opens the underlying archive with the provided OpenMode,
@@ -400,9 +958,10 @@ impl<'a> Archive<'a> {
     /// # }
     /// ```
     ///
-    /// # Panics
+    /// # Errors
     ///
-    /// Panics if `path` contains nul values.
+    /// Returns an error with [`Code::Nul`](crate::error::Code::Nul) if `self.filename` or the
+    /// password contain nul values.
     pub fn break_open<M: OpenMode>(
         self,
         error: Option<&mut Option<UnrarError>>,
@@ -411,7 +970,9 @@ impl<'a> Archive<'a> {
         self.open(Some(&mut recovered))
             .or_else(|x| match recovered {
                 Some(archive) => {
-                    error.map(|error| *error = Some(x));
+                    if let Some(error) = error {
+                        *error = Some(x);
+                    }
                     Ok(archive)
                 }
                 None => Err(x),
@@ -432,6 +993,20 @@ fn get_rar_extension<T: AsRef<Path>>(path: T) -> Option<String> {
     })
 }
 
+/// the volume after `volume` of a multipart archive, with either the `.partN.rar` naming of
+/// RAR 5.0 or the old naming where `.rar` is followed by `.r00`, `.r01` and so on
+pub(crate) fn next_part(volume: &Path) -> Option<PathBuf> {
+    let archive = Archive::new(volume);
+    let extension = get_rar_extension(volume)?;
+    let Some(captures) = multipart_extension().captures(&extension) else {
+        return extension
+            .ends_with(".rar")
+            .then(|| volume.with_extension("r00"));
+    };
+    let number: i32 = captures.get(2)?.as_str().parse().ok()?;
+    archive.nth_part(number.checked_add(1)?)
+}
+
 pub fn is_archive(s: &Path) -> bool {
     get_rar_extension(s).is_some_and(|e| extension().is_match(&e))
 }
@@ -443,7 +1018,7 @@ pub fn is_multipart(s: &Path) -> bool {
 #[cfg(test)]
 mod tests {
     use super::Archive;
-    use std::path::PathBuf;
+    use std::path::{Path, PathBuf};
 
     #[test]
     fn glob() {
@@ -512,17 +1087,31 @@ mod tests {
     }
 
     #[test]
-    fn is_archive() {
-        assert_eq!(super::is_archive(&PathBuf::from("archive.rar")), true);
-        assert_eq!(super::is_archive(&PathBuf::from("archive.part1.rar")), true);
+    fn next_part() {
         assert_eq!(
-            super::is_archive(&PathBuf::from("archive.part100.rar")),
-            true
+            super::next_part(Path::new("arc.part09.rar")),
+            Some(PathBuf::from("arc.part10.rar"))
         );
-        assert_eq!(super::is_archive(&PathBuf::from("archive.r10")), true);
-        assert_eq!(super::is_archive(&PathBuf::from("archive.part1rar")), false);
-        assert_eq!(super::is_archive(&PathBuf::from("archive.rar\n")), false);
-        assert_eq!(super::is_archive(&PathBuf::from("archive.zip")), false);
+        assert_eq!(
+            super::next_part(Path::new("archive.rar")),
+            Some(PathBuf::from("archive.r00"))
+        );
+        assert_eq!(
+            super::next_part(Path::new("archive.r00")),
+            Some(PathBuf::from("archive.r01"))
+        );
+        assert_eq!(super::next_part(Path::new("random_string")), None);
+    }
+
+    #[test]
+    fn is_archive() {
+        assert!(super::is_archive(&PathBuf::from("archive.rar")));
+        assert!(super::is_archive(&PathBuf::from("archive.part1.rar")));
+        assert!(super::is_archive(&PathBuf::from("archive.part100.rar")));
+        assert!(super::is_archive(&PathBuf::from("archive.r10")));
+        assert!(!super::is_archive(&PathBuf::from("archive.part1rar")));
+        assert!(!super::is_archive(&PathBuf::from("archive.rar\n")));
+        assert!(!super::is_archive(&PathBuf::from("archive.zip")));
     }
 
     #[test]
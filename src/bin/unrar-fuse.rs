@@ -0,0 +1,68 @@
+//! Mounts an archive as a read-only file system, see the `fuse` module of the library.
+//!
+//! Usage: `unrar-fuse [--password <password>] [--cache <MiB>] <archive> <mountpoint>`
+
+#[cfg(target_os = "linux")]
+fn main() {
+    use std::ffi::OsString;
+    use unrar::{fuse::RarFs, Archive};
+
+    const USAGE: &str =
+        "usage: unrar-fuse [--password <password>] [--cache <MiB>] <archive> <mountpoint>";
+
+    let mut password: Option<OsString> = None;
+    let mut cache = None;
+    let mut paths = Vec::new();
+    let mut args = std::env::args_os().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.to_str() {
+            Some("-p" | "--password") => password = args.next(),
+            Some("--cache") => {
+                let mib = args
+                    .next()
+                    .and_then(|mib| mib.to_str()?.parse::<usize>().ok());
+                let Some(mib) = mib else {
+                    eprintln!("{USAGE}");
+                    std::process::exit(2);
+                };
+                cache = Some(mib << 20);
+            }
+            Some("-h" | "--help") => {
+                println!("{USAGE}");
+                return;
+            }
+            _ => paths.push(arg),
+        }
+    }
+    let [file, mountpoint] = &paths[..] else {
+        eprintln!("{USAGE}");
+        std::process::exit(2);
+    };
+
+    let password = password.map(|password| password.into_encoded_bytes());
+    let archive = match &password {
+        Some(password) => Archive::with_password(file, password),
+        None => Archive::new(file),
+    };
+    let fs = match RarFs::new(archive.as_first_part()) {
+        Ok(fs) => fs,
+        Err(e) => {
+            eprintln!("error: {e}");
+            std::process::exit(1);
+        }
+    };
+    let fs = match cache {
+        Some(bytes) => fs.with_cache_size(bytes),
+        None => fs,
+    };
+    if let Err(e) = fs.mount(mountpoint) {
+        eprintln!("error: cannot mount {}: {e}", mountpoint.to_string_lossy());
+        std::process::exit(1);
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn main() {
+    eprintln!("error: unrar-fuse only runs on Linux");
+    std::process::exit(1);
+}
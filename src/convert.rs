@@ -0,0 +1,216 @@
+//! Converting RAR archives to tar and zip archives, available with the `convert` feature.
+//!
+//! The entries are decompressed straight into the new archive, in the order they appear in
+//! the RAR archive, without touching the disk. Directories, links, modification times and
+//! permissions are carried over; NTFS streams and other service headers are not, as unrar
+//! does not hand them out.
+//!
+//! # Example
+//!
+//! ```
+//! use unrar::{convert, Archive};
+//!
+//! let archive = Archive::new("data/multi.rar").open_for_processing().unwrap();
+//! let tar = convert::to_tar(archive, Vec::new()).unwrap();
+//! let names: Vec<_> = tar::Archive::new(&tar[..])
+//!     .entries()
+//!     .unwrap()
+//!     .map(|entry| entry.unwrap().path().unwrap().into_owned())
+//!     .collect();
+//! assert_eq!(names.len(), 5);
+//! ```
+
+use crate::error::{Code, UnrarError, UnrarResult, When};
+use crate::open_archive::{CursorBeforeHeader, FileHeader, Link, LinkKind, OpenArchive, Process};
+use crate::sink::{self, Sink};
+use std::io::{self, Read, Seek, Write};
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+use zip::write::SimpleFileOptions;
+
+/// Writes all entries of `archive` into a tar archive written to `writer`, which is
+/// returned once the tar archive is complete.
+///
+/// Entries are written as GNU tar entries, using its extensions for long names. File copies
+/// become hard links to the file they copy.
+///
+/// # Errors
+///
+/// Like [`Archive::extract_all`](crate::Archive::extract_all), with errors of `writer`
+/// standing in for those of the sink. A file whose data turns out not to match its size
+/// leaves the tar archive incomplete.
+pub fn to_tar<W: Write>(
+    archive: OpenArchive<Process, CursorBeforeHeader>,
+    writer: W,
+) -> UnrarResult<W> {
+    let mut sink = TarSink {
+        builder: tar::Builder::new(writer),
+        size: 0,
+        written: 0,
+    };
+    sink::extract(archive, &mut sink)?;
+    sink.builder
+        .into_inner()
+        .map_err(|e| UnrarError::io(Code::EClose, When::Process, e))
+}
+
+/// Writes all entries of `archive` into a zip archive written to `writer`, which is
+/// returned once the zip archive is complete.
+///
+/// Files are compressed with deflate. Hard links and file copies become copies of the file
+/// they link to, which zip has no notion of, which is why `writer` needs to be readable.
+///
+/// # Errors
+///
+/// Like [`Archive::extract_all`](crate::Archive::extract_all), with errors of `writer`
+/// standing in for those of the sink.
+pub fn to_zip<W: Read + Write + Seek>(
+    archive: OpenArchive<Process, CursorBeforeHeader>,
+    writer: W,
+) -> UnrarResult<W> {
+    let mut sink = ZipSink {
+        zip: zip::ZipWriter::new(writer),
+    };
+    sink::extract(archive, &mut sink)?;
+    sink.zip
+        .finish()
+        .map_err(|e| UnrarError::io(Code::EClose, When::Process, e.into()))
+}
+
+struct TarSink<W: Write> {
+    builder: tar::Builder<W>,
+    /// the size of the file started last, as its header tells
+    size: u64,
+    /// the bytes of the file started last written so far
+    written: u64,
+}
+
+impl<W: Write> TarSink<W> {
+    fn header(entry: &FileHeader, kind: tar::EntryType) -> tar::Header {
+        let mut header = tar::Header::new_gnu();
+        header.set_entry_type(kind);
+        header.set_mode(entry.permissions());
+        let mtime = entry
+            .modified()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok());
+        header.set_mtime(mtime.map_or(0, |mtime| mtime.as_secs()));
+        header.set_size(0);
+        header
+    }
+}
+
+impl<W: Write> Sink for TarSink<W> {
+    fn create_dir(&mut self, header: &FileHeader) -> io::Result<()> {
+        let mut entry = Self::header(header, tar::EntryType::Directory);
+        self.builder
+            .append_data(&mut entry, &header.filename, io::empty())
+    }
+
+    fn begin_file(&mut self, header: &FileHeader) -> io::Result<()> {
+        let mut entry = Self::header(header, tar::EntryType::Regular);
+        entry.set_size(header.unpacked_size);
+        // tar needs the size up front, so only the header is written here and the data is
+        // written as it is decompressed
+        self.builder
+            .append_data(&mut entry, &header.filename, io::empty())?;
+        self.size = header.unpacked_size;
+        self.written = 0;
+        Ok(())
+    }
+
+    fn write_chunk(&mut self, data: &[u8]) -> io::Result<()> {
+        self.written += data.len() as u64;
+        if self.written > self.size {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "the file is larger than its header tells",
+            ));
+        }
+        self.builder.get_mut().write_all(data)
+    }
+
+    fn end_file(&mut self, _: &FileHeader) -> io::Result<()> {
+        if self.written != self.size {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "the file is smaller than its header tells",
+            ));
+        }
+        // entries take up whole blocks of 512 bytes
+        let padding = (512 - self.written % 512) % 512;
+        self.builder
+            .get_mut()
+            .write_all(&[0; 512][..padding as usize])
+    }
+
+    fn create_link(&mut self, header: &FileHeader, link: &Link) -> io::Result<()> {
+        let kind = match link.kind {
+            LinkKind::Hardlink | LinkKind::FileCopy => tar::EntryType::Link,
+            _ if link.target.as_os_str().is_empty() => return Err(sink::unknown_target()),
+            _ => tar::EntryType::Symlink,
+        };
+        let mut entry = Self::header(header, kind);
+        self.builder
+            .append_link(&mut entry, &header.filename, &link.target)
+    }
+}
+
+struct ZipSink<W: Write + Seek> {
+    zip: zip::ZipWriter<W>,
+}
+
+impl<W: Write + Seek> ZipSink<W> {
+    fn options(entry: &FileHeader) -> SimpleFileOptions {
+        // zip keeps the MS-DOS time in local time just like RAR does
+        let time = entry.file_time;
+        let options = SimpleFileOptions::default()
+            .unix_permissions(entry.permissions())
+            .large_file(entry.unpacked_size >= u32::MAX as u64);
+        match zip::DateTime::try_from_msdos((time >> 16) as u16, time as u16) {
+            Ok(time) => options.last_modified_time(time),
+            Err(_) => options,
+        }
+    }
+}
+
+/// the name of `path` in a zip archive, which always uses slashes as separators
+fn zip_name(path: &Path) -> String {
+    let name = path.to_string_lossy();
+    match cfg!(windows) {
+        true => name.replace('\\', "/"),
+        false => name.into_owned(),
+    }
+}
+
+impl<W: Read + Write + Seek> Sink for ZipSink<W> {
+    fn create_dir(&mut self, header: &FileHeader) -> io::Result<()> {
+        let name = zip_name(&header.filename);
+        Ok(self.zip.add_directory(name, Self::options(header))?)
+    }
+
+    fn begin_file(&mut self, header: &FileHeader) -> io::Result<()> {
+        let name = zip_name(&header.filename);
+        Ok(self.zip.start_file(name, Self::options(header))?)
+    }
+
+    fn write_chunk(&mut self, data: &[u8]) -> io::Result<()> {
+        self.zip.write_all(data)
+    }
+
+    fn end_file(&mut self, _: &FileHeader) -> io::Result<()> {
+        // the file is finished along with the next one or the archive
+        Ok(())
+    }
+
+    fn create_link(&mut self, header: &FileHeader, link: &Link) -> io::Result<()> {
+        let name = zip_name(&header.filename);
+        let target = zip_name(&link.target);
+        match link.kind {
+            LinkKind::Hardlink | LinkKind::FileCopy => {
+                Ok(self.zip.deep_copy_file(&target, &name)?)
+            }
+            _ if target.is_empty() => Err(sink::unknown_target()),
+            _ => Ok(self.zip.add_symlink(name, target, Self::options(header))?),
+        }
+    }
+}
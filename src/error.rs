@@ -1,14 +1,17 @@
 #![allow(missing_docs)]
 
 use super::*;
+use crate::limits::Limit;
 use std::error;
 use std::ffi;
 use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
 use std::result::Result;
 
-
 #[derive(PartialEq, Eq, Debug, Clone, Copy)]
 #[repr(i32)]
+#[non_exhaustive]
 pub enum Code {
     Success = native::ERAR_SUCCESS,
     EndArchive = native::ERAR_END_ARCHIVE,
@@ -32,6 +35,17 @@ pub enum Code {
     // record without its source file.
     EReference = native::ERAR_EREFERENCE,
     BadPassword = native::ERAR_BAD_PASSWORD,
+    /// An error code this crate does not know about, e.g. from a newer unrar version.
+    Other(i32),
+    /// A path or password passed to this crate contains a nul value and cannot be handed
+    /// to the unrar library. See [`UnrarError::nul_error`].
+    Nul,
+    /// One of the [`ExtractLimits`] was exceeded.
+    /// See [`UnrarError::limit`].
+    LimitExceeded,
+    /// The data of an entry is compressed or encrypted where it has to be stored as is, see
+    /// [`OpenArchive::open_seekable`](crate::OpenArchive::open_seekable).
+    NotStored,
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
@@ -42,42 +56,102 @@ pub enum When {
 }
 
 impl Code {
-    pub fn from(code: i32) -> Option<Self> {
+    /// Maps a native unrar return code, codes unknown to this crate become [`Code::Other`].
+    pub fn from(code: i32) -> Self {
         use Code::*;
         match code {
-            native::ERAR_SUCCESS => Some(Success),
-            native::ERAR_END_ARCHIVE => Some(EndArchive),
-            native::ERAR_NO_MEMORY => Some(NoMemory),
-            native::ERAR_BAD_DATA => Some(BadData),
-            native::ERAR_BAD_ARCHIVE => Some(BadArchive),
-            native::ERAR_UNKNOWN_FORMAT => Some(UnknownFormat),
-            native::ERAR_EOPEN => Some(EOpen),
-            native::ERAR_ECREATE => Some(ECreate),
-            native::ERAR_ECLOSE => Some(EClose),
-            native::ERAR_EREAD => Some(ERead),
-            native::ERAR_EWRITE => Some(EWrite),
-            native::ERAR_SMALL_BUF => Some(SmallBuf),
-            native::ERAR_UNKNOWN => Some(Unknown),
-            native::ERAR_MISSING_PASSWORD => Some(MissingPassword),
-            native::ERAR_EREFERENCE => Some(EReference),
-            native::ERAR_BAD_PASSWORD => Some(BadPassword),
-            _ => None,
+            native::ERAR_SUCCESS => Success,
+            native::ERAR_END_ARCHIVE => EndArchive,
+            native::ERAR_NO_MEMORY => NoMemory,
+            native::ERAR_BAD_DATA => BadData,
+            native::ERAR_BAD_ARCHIVE => BadArchive,
+            native::ERAR_UNKNOWN_FORMAT => UnknownFormat,
+            native::ERAR_EOPEN => EOpen,
+            native::ERAR_ECREATE => ECreate,
+            native::ERAR_ECLOSE => EClose,
+            native::ERAR_EREAD => ERead,
+            native::ERAR_EWRITE => EWrite,
+            native::ERAR_SMALL_BUF => SmallBuf,
+            native::ERAR_UNKNOWN => Unknown,
+            native::ERAR_MISSING_PASSWORD => MissingPassword,
+            native::ERAR_EREFERENCE => EReference,
+            native::ERAR_BAD_PASSWORD => BadPassword,
+            code => Other(code),
         }
     }
+
+    /// does this code denote a failed file system operation
+    fn is_io(self) -> bool {
+        use Code::*;
+        matches!(self, EOpen | ECreate | EClose | ERead | EWrite)
+    }
 }
 
-#[derive(PartialEq)]
+/// An error returned by the unrar library, along with the context it occurred in.
+///
+/// Besides the [`code`](UnrarError::code) and [`when`](UnrarError::when) it occurred, the
+/// error remembers the archive entry that was being processed, the volume that was being
+/// read and, for failed file system operations, the OS error reported at that time.
 pub struct UnrarError {
     pub code: Code,
     pub when: When,
+    filename: Option<PathBuf>,
+    volume: Option<PathBuf>,
+    source: Option<Source>,
+}
+
+#[derive(Debug)]
+enum Source {
+    Io(io::Error),
+    Nul(NulError),
+    Limit(Limit),
+}
+
+impl PartialEq for Source {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Source::Io(a), Source::Io(b)) => a.kind() == b.kind(),
+            (Source::Nul(a), Source::Nul(b)) => a == b,
+            (Source::Limit(a), Source::Limit(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl PartialEq for UnrarError {
+    fn eq(&self, other: &Self) -> bool {
+        self.code == other.code
+            && self.when == other.when
+            && self.filename == other.filename
+            && self.volume == other.volume
+            && self.source == other.source
+    }
 }
 
-impl std::error::Error for UnrarError {}
+impl std::error::Error for UnrarError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self.source.as_ref()? {
+            Source::Io(e) => Some(e),
+            Source::Nul(e) => Some(e),
+            Source::Limit(_) => None,
+        }
+    }
+}
 
 impl fmt::Debug for UnrarError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{:?}@{:?}", self.code, self.when)?;
-        write!(f, " ({})", self)
+        write!(f, " ({})", self)?;
+        if let Some(filename) = &self.filename {
+            write!(f, " entry: {:?}", filename)?;
+        }
+        if let Some(volume) = &self.volume {
+            write!(f, " volume: {:?}", volume)?;
+        }
+        if let Some(source) = error::Error::source(self) {
+            write!(f, " caused by: {}", source)?;
+        }
+        Ok(())
     }
 }
 
@@ -106,21 +180,146 @@ impl fmt::Display for UnrarError {
             (Unknown, _) => write!(f, "Unknown error"),
             (EndArchive, _) => write!(f, "Archive end"),
             (Success, _) => write!(f, "Success"),
+            (Other(code), _) => write!(f, "Unknown error code {code}"),
+            (Nul, _) => write!(f, "Unexpected nul value in input"),
+            (LimitExceeded, _) => match self.limit() {
+                Some(limit) => write!(f, "Exceeded the {limit}"),
+                None => write!(f, "Exceeded a limit"),
+            },
+            (NotStored, _) => write!(f, "Entry is compressed or encrypted"),
         }
     }
 }
 
 impl UnrarError {
     pub fn from(code: Code, when: When) -> Self {
-        UnrarError { code, when }
+        UnrarError {
+            code,
+            when,
+            filename: None,
+            volume: None,
+            source: None,
+        }
+    }
+
+    /// the archive entry that was being read or processed when the error occurred
+    pub fn filename(&self) -> Option<&Path> {
+        self.filename.as_deref()
+    }
+
+    /// the archive volume that was being read when the error occurred.
+    ///
+    /// If a subsequent volume could not be opened, this is the path of the missing volume.
+    pub fn volume(&self) -> Option<&Path> {
+        self.volume.as_deref()
+    }
+
+    /// The OS error of a failed file system operation, i.e. for [`Code::EOpen`],
+    /// [`Code::ECreate`], [`Code::EClose`], [`Code::ERead`] and [`Code::EWrite`].
+    ///
+    /// This is the OS error (`errno` on Unix) that was current right after the unrar library
+    /// returned. It is cleared before calling the library, so it is not left over from
+    /// before, but it is not necessarily set by the operation that failed either: unrar may
+    /// fail without the OS reporting an error, or make other calls before returning. Also
+    /// available via [`source`](std::error::Error::source).
+    pub fn io_error(&self) -> Option<&io::Error> {
+        match self.source.as_ref()? {
+            Source::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+
+    /// the nul value found in a path or password, for [`Code::Nul`]
+    pub fn nul_error(&self) -> Option<&NulError> {
+        match self.source.as_ref()? {
+            Source::Nul(e) => Some(e),
+            _ => None,
+        }
+    }
+
+    /// the limit that was exceeded, for [`Code::LimitExceeded`]
+    pub fn limit(&self) -> Option<Limit> {
+        match self.source.as_ref()? {
+            Source::Limit(limit) => Some(*limit),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn limit_exceeded(limit: Limit, when: When) -> Self {
+        let mut this = UnrarError::from(Code::LimitExceeded, when);
+        this.source = Some(Source::Limit(limit));
+        this
+    }
+
+    /// an error for an I/O operation done by this crate rather than by unrar
+    pub(crate) fn io(code: Code, when: When, error: io::Error) -> Self {
+        let mut this = UnrarError::from(code, when);
+        this.source = Some(Source::Io(error));
+        this
+    }
+
+    pub(crate) fn nul(error: NulError, when: When) -> Self {
+        let mut this = UnrarError::from(Code::Nul, when);
+        this.source = Some(Source::Nul(error));
+        this
+    }
+
+    pub(crate) fn with_filename(mut self, filename: &Path) -> Self {
+        self.filename = Some(filename.to_path_buf());
+        self
+    }
+
+    pub(crate) fn with_volume(mut self, volume: &Path) -> Self {
+        self.volume = Some(volume.to_path_buf());
+        self
+    }
+
+    /// attaches `error` if the error code denotes a failed file system operation
+    pub(crate) fn with_os_error(mut self, error: io::Error) -> Self {
+        if self.code.is_io() && error.raw_os_error().is_some_and(|code| code != 0) {
+            self.source = Some(Source::Io(error));
+        }
+        self
     }
 }
 
 pub type UnrarResult<T> = Result<T, UnrarError>;
 
-#[derive(Debug)]
+/// clears the OS error before calling the unrar library, so that one current afterwards
+/// was not left over from before
+pub(crate) fn clear_os_error() {
+    #[cfg(target_os = "linux")]
+    unsafe {
+        *libc::__errno_location() = 0
+    };
+    #[cfg(any(target_os = "macos", target_os = "ios", target_os = "freebsd"))]
+    unsafe {
+        *libc::__error() = 0
+    };
+    #[cfg(any(target_os = "android", target_os = "netbsd", target_os = "openbsd"))]
+    unsafe {
+        *libc::__errno() = 0
+    };
+    #[cfg(windows)]
+    unsafe {
+        winapi::um::errhandlingapi::SetLastError(0)
+    };
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub struct NulError(usize);
 
+impl NulError {
+    /// the position of the nul value in the input
+    pub fn nul_position(&self) -> usize {
+        self.0
+    }
+
+    pub(crate) fn at(position: usize) -> Self {
+        NulError(position)
+    }
+}
+
 impl fmt::Display for NulError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "nul value found at position: {}", self.0)
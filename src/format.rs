@@ -0,0 +1,790 @@
+use crate::open_archive::VolumeInfo;
+use std::ffi::OsStr;
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// the signature shared by RAR 1.5 and later, followed by the format version
+const SIGNATURE: &[u8] = b"Rar!\x1a\x07";
+/// the signature of RAR 1.4
+const SIGNATURE14: &[u8] = b"RE~^";
+/// the largest SFX module unrar looks for an archive in, `MAXSFXSIZE` in unrar
+const MAX_SFX_SIZE: usize = 0x200000;
+/// how much of a file is inspected: the SFX module plus the headers up to the first entry
+const SCAN_SIZE: u64 = MAX_SFX_SIZE as u64 + 0x10000;
+/// how much of a file is inspected first, which is enough unless it is self-extracting
+const HEAD_SIZE: u64 = 0x10000;
+
+/// The version of the RAR format an archive is stored in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RarFormat {
+    /// RAR 1.4, with the signature `RE~^`
+    Rar14,
+    /// RAR 1.5 to 4.x, with the signature `Rar!\x1a\x07\x00`
+    Rar15to4,
+    /// RAR 5.0 and later, with the signature `Rar!\x1a\x07\x01\x00`
+    Rar5,
+    /// RAR 5.0 with entries compressed by the algorithm of RAR 7.0, which older versions of
+    /// unrar cannot extract.
+    ///
+    /// The archive format is the same as [`Rar5`](RarFormat::Rar5) and the compression
+    /// version is recorded per entry, so this is reported if the first entry uses it.
+    Rar7,
+}
+
+/// The format of an archive as determined by [`detect`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Format {
+    /// the version of the RAR format
+    pub version: RarFormat,
+    /// where the archive starts. Non-zero for self-extracting archives, which start with an
+    /// executable SFX module.
+    pub offset: u64,
+    /// whether the archive is a volume of a multipart archive and if so, whether it is the
+    /// first one. `None` if that is unknown because the headers are encrypted or damaged.
+    pub volume: Option<VolumeInfo>,
+}
+
+impl Format {
+    /// is this a self-extracting archive
+    pub fn is_sfx(&self) -> bool {
+        self.offset > 0
+    }
+}
+
+/// Where the optional parts of a RAR 5.0 archive are, as recorded in its main header.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Locator {
+    /// the offset of the quick open record within the volume, see
+    /// [`OpenArchive::has_quick_open`](crate::OpenArchive::has_quick_open)
+    pub quick_open: Option<u64>,
+    /// the offset of the recovery record within the volume
+    pub recovery: Option<u64>,
+}
+
+/// The authenticity verification data of a RAR 2.x or 3.x archive, see
+/// [`OpenArchive::signature`](crate::OpenArchive::signature).
+///
+/// The AV data itself is encrypted, so only the sign block that may accompany it tells who
+/// created the archive and when.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthenticityInfo {
+    /// the name of the registered user who created the archive
+    pub creator: Option<String>,
+    /// the name of the archive when it was created
+    pub archive_name: Option<String>,
+    /// when the archive was created, in MS-DOS format like
+    /// [`FileHeader::file_time`](crate::FileHeader::file_time)
+    pub creation_time: Option<u32>,
+    /// the AV and sign blocks as stored in the archive, including their headers
+    pub raw: Vec<u8>,
+}
+
+/// What is known about an archive from its signature and main header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct MainHeader {
+    pub(crate) format: Format,
+    pub(crate) locator: Option<Locator>,
+    /// the name the archive was created with
+    pub(crate) name: Option<String>,
+    pub(crate) created: Option<SystemTime>,
+    pub(crate) signature: Option<AuthenticityInfo>,
+    /// where the AV data is, as recorded in the main header of RAR 1.5 to 4.x archives
+    av_position: Option<u64>,
+}
+
+impl MainHeader {
+    fn new(version: RarFormat, offset: u64, volume: Option<VolumeInfo>) -> Self {
+        MainHeader {
+            format: Format {
+                version,
+                offset,
+                volume,
+            },
+            locator: None,
+            name: None,
+            created: None,
+            signature: None,
+            av_position: None,
+        }
+    }
+
+    /// reads the main header of the archive at `path`, which unrar has already opened.
+    ///
+    /// Unlike [`detect`], this does not insist on an intact main header, since unrar has
+    /// accepted the archive anyway.
+    pub(crate) fn read(path: &Path) -> io::Result<Self> {
+        let mut file = File::open(path)?;
+        let mut bytes = Vec::new();
+        // most archives start right at the beginning, so avoid reading an SFX module's worth
+        (&mut file).take(HEAD_SIZE).read_to_end(&mut bytes)?;
+        if !bytes.starts_with(SIGNATURE) && !bytes.starts_with(SIGNATURE14) {
+            (&mut file)
+                .take(SCAN_SIZE - HEAD_SIZE)
+                .read_to_end(&mut bytes)?;
+        }
+        let scan = || 0..bytes.len().min(MAX_SFX_SIZE);
+        let mut header = scan()
+            .find_map(|offset| parse(&bytes, offset))
+            .or_else(|| {
+                scan().find_map(|offset| {
+                    let version = signature(&bytes[offset..])?.0;
+                    Some(MainHeader::new(version, offset as u64, None))
+                })
+            })
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "no RAR signature found"))?;
+        if let Some(position) = header.av_position {
+            let mut blocks = Vec::new();
+            file.seek(SeekFrom::Start(position))?;
+            file.take(HEAD_SIZE).read_to_end(&mut blocks)?;
+            header.signature = authenticity(&blocks);
+        }
+        Ok(header)
+    }
+}
+
+/// What [`detect`] inspects: a file or its contents.
+#[derive(Debug, Clone, Copy)]
+pub enum Input<'a> {
+    /// the file at this path
+    Path(&'a Path),
+    /// the contents of a file, at least its beginning
+    Bytes(&'a [u8]),
+}
+
+impl<'a> From<&'a Path> for Input<'a> {
+    fn from(path: &'a Path) -> Self {
+        Input::Path(path)
+    }
+}
+
+impl<'a> From<&'a PathBuf> for Input<'a> {
+    fn from(path: &'a PathBuf) -> Self {
+        Input::Path(path)
+    }
+}
+
+impl<'a> From<&'a OsStr> for Input<'a> {
+    fn from(path: &'a OsStr) -> Self {
+        Input::Path(path.as_ref())
+    }
+}
+
+impl<'a> From<&'a str> for Input<'a> {
+    fn from(path: &'a str) -> Self {
+        Input::Path(path.as_ref())
+    }
+}
+
+impl<'a> From<&'a [u8]> for Input<'a> {
+    fn from(bytes: &'a [u8]) -> Self {
+        Input::Bytes(bytes)
+    }
+}
+
+impl<'a, const N: usize> From<&'a [u8; N]> for Input<'a> {
+    fn from(bytes: &'a [u8; N]) -> Self {
+        Input::Bytes(bytes)
+    }
+}
+
+impl<'a> From<&'a Vec<u8>> for Input<'a> {
+    fn from(bytes: &'a Vec<u8>) -> Self {
+        Input::Bytes(bytes)
+    }
+}
+
+/// Detects whether a file is a RAR archive by its contents rather than its extension.
+///
+/// Strings are taken as paths, see [`Input`] for everything else that can be inspected.
+/// Returns `None` if the input is not a RAR archive or cannot be read.
+///
+/// Like unrar, this looks for the archive within the first 2 MB, where the SFX module of a
+/// self-extracting archive would end. Unless the archive starts right at the beginning, its
+/// main header must be intact, so signatures that merely happen to be part of an SFX module
+/// are not mistaken for an archive.
+///
+/// # Example
+///
+/// ```
+/// use unrar::{RarFormat, VolumeInfo};
+///
+/// let format = unrar::detect("data/archive.part1.rar").unwrap();
+/// assert_eq!(format.version, RarFormat::Rar15to4);
+/// assert_eq!(format.volume, Some(VolumeInfo::First));
+///
+/// assert_eq!(unrar::detect(b"PK\x03\x04"), None);
+/// ```
+pub fn detect<'a, I: Into<Input<'a>>>(input: I) -> Option<Format> {
+    match input.into() {
+        Input::Path(path) => {
+            let mut bytes = Vec::new();
+            File::open(path)
+                .ok()?
+                .take(SCAN_SIZE)
+                .read_to_end(&mut bytes)
+                .ok()?;
+            detect_bytes(&bytes)
+        }
+        Input::Bytes(bytes) => detect_bytes(bytes),
+    }
+}
+
+fn detect_bytes(bytes: &[u8]) -> Option<Format> {
+    (0..bytes.len().min(MAX_SFX_SIZE))
+        .find_map(|offset| parse(bytes, offset))
+        .map(|main| main.format)
+}
+
+/// matches the signature at the beginning of `bytes` and returns the headers following it
+fn signature(bytes: &[u8]) -> Option<(RarFormat, &[u8])> {
+    if let Some(headers) = bytes.strip_prefix(SIGNATURE14) {
+        return Some((RarFormat::Rar14, headers));
+    }
+    match bytes.strip_prefix(SIGNATURE)? {
+        [0, headers @ ..] => Some((RarFormat::Rar15to4, headers)),
+        [1, 0, headers @ ..] => Some((RarFormat::Rar5, headers)),
+        _ => None,
+    }
+}
+
+/// parses the archive found at `offset` in the input
+fn parse(input: &[u8], offset: usize) -> Option<MainHeader> {
+    let bytes = &input[offset..];
+    // unrar only looks for a RAR 1.4 archive after an SFX module that says so
+    let sfx14 = offset == 0 || input.get(28..32) == Some(b"RSFX");
+    let (version, headers) = signature(bytes)?;
+    let offset = offset as u64;
+    let main = match version {
+        RarFormat::Rar14 if !sfx14 => return None,
+        RarFormat::Rar14 => main14(bytes, offset),
+        RarFormat::Rar15to4 => main15(headers, offset),
+        _ => main50(headers, offset),
+    };
+    match main {
+        Some(main) => Some(main),
+        None if offset == 0 => Some(MainHeader::new(version, offset, None)),
+        None => None,
+    }
+}
+
+/// parses the main header of a RAR 1.4 archive, which has no CRC.
+///
+/// Returns `None` if it is truncated.
+fn main14(bytes: &[u8], offset: u64) -> Option<MainHeader> {
+    const MHD_VOLUME: u8 = 0x1;
+    const LHD_SPLIT_BEFORE: u8 = 0x1;
+
+    let mut reader = Reader(bytes.get(SIGNATURE14.len()..)?);
+    // the size of the main header includes the signature
+    let size = reader.u16()? as usize;
+    if size < 7 {
+        return None;
+    }
+    let flags = reader.u8()?;
+    let volume = if flags & MHD_VOLUME == 0 {
+        VolumeInfo::None
+    } else {
+        // there is no first volume flag, so go by the first file like unrar
+        match bytes.get(size..).and_then(|file| file.get(17)) {
+            Some(flags) if flags & LHD_SPLIT_BEFORE != 0 => VolumeInfo::Subsequent,
+            _ => VolumeInfo::First,
+        }
+    };
+    Some(MainHeader::new(RarFormat::Rar14, offset, Some(volume)))
+}
+
+/// parses the main header of a RAR 1.5 to 4.x archive.
+///
+/// Returns `None` if there is no intact main header.
+fn main15(headers: &[u8], offset: u64) -> Option<MainHeader> {
+    const MAIN: u8 = 0x73;
+    const FILE: u8 = 0x74;
+    const SERVICE: u8 = 0x7a;
+    const END: u8 = 0x7b;
+    const MHD_VOLUME: u16 = 0x1;
+    const MHD_PASSWORD: u16 = 0x80;
+    const MHD_FIRSTVOLUME: u16 = 0x100;
+    const LHD_SPLIT_BEFORE: u16 = 0x1;
+
+    let (main, mut next) = Block15::parse(headers)?;
+    if main.kind != MAIN || !main.intact {
+        return None;
+    }
+    // like unrar, an archive is signed if the main header says where its AV data is
+    let mut body = Reader(&headers[7..main.size]);
+    let av_position = body
+        .u16()
+        .zip(body.u32())
+        .map_or(0, |(high, low)| (high as u64) << 32 | low as u64);
+    let volume = |volume| {
+        let mut header = MainHeader::new(RarFormat::Rar15to4, offset, Some(volume));
+        header.av_position = (av_position != 0).then_some(av_position);
+        Some(header)
+    };
+    if main.flags & MHD_VOLUME == 0 {
+        return volume(VolumeInfo::None);
+    }
+    let mut first = main.flags & MHD_FIRSTVOLUME != 0;
+    // RAR 2.x did not set the first volume flag, so like unrar, go by the first file instead.
+    // This is only possible if the headers are not encrypted.
+    if main.flags & MHD_PASSWORD != 0 {
+        next = None;
+    }
+    while let Some((block, rest)) = next.and_then(Block15::parse) {
+        if !block.intact {
+            break;
+        }
+        match block.kind {
+            FILE | SERVICE => {
+                first = block.flags & LHD_SPLIT_BEFORE == 0;
+                if block.kind == FILE {
+                    break;
+                }
+            }
+            END => break,
+            _ => {}
+        }
+        next = rest;
+    }
+    volume(if first {
+        VolumeInfo::First
+    } else {
+        VolumeInfo::Subsequent
+    })
+}
+
+/// collects the AV and sign blocks of a RAR 1.5 to 4.x archive at the beginning of `blocks`
+fn authenticity(mut blocks: &[u8]) -> Option<AuthenticityInfo> {
+    const AV: u8 = 0x76;
+    const SIGN: u8 = 0x79;
+    const SERVICE: u8 = 0x7a;
+    const LHD_LARGE: u16 = 0x100;
+
+    let mut info = AuthenticityInfo {
+        creator: None,
+        archive_name: None,
+        creation_time: None,
+        raw: Vec::new(),
+    };
+    // unrar does not check the CRC of these blocks, since old versions did not set it properly
+    while let Some((block, rest)) = Block15::parse(blocks) {
+        let mut body = Reader(&blocks[7..block.size]);
+        match block.kind {
+            AV => {}
+            SIGN => {
+                info.creation_time = body.u32();
+                let names = body.u16().zip(body.u16()).and_then(|(archive, user)| {
+                    Some((body.take(archive as usize)?, body.take(user as usize)?))
+                });
+                if let Some((archive, user)) = names {
+                    info.archive_name = Some(String::from_utf8_lossy(archive).into_owned());
+                    info.creator = Some(String::from_utf8_lossy(user).into_owned());
+                }
+            }
+            // RAR 3.x stores the AV data in a service header
+            SERVICE => {
+                let start = match block.flags & LHD_LARGE {
+                    0 => 25,
+                    _ => 33,
+                };
+                let name = body.0.get(19..).and_then(|fields| {
+                    let size = Reader(fields).u16()? as usize;
+                    body.0.get(start..start + size)
+                });
+                if name != Some(&b"AV"[..]) {
+                    break;
+                }
+            }
+            _ => break,
+        }
+        let len = rest.map_or(block.size, |rest| blocks.len() - rest.len());
+        info.raw.extend_from_slice(&blocks[..len]);
+        match rest {
+            Some(rest) => blocks = rest,
+            None => break,
+        }
+    }
+    (!info.raw.is_empty()).then_some(info)
+}
+
+/// parses the main header of a RAR 5.0 archive along with its extra records and the
+/// headers up to the first file.
+///
+/// Returns `None` if there is no intact main header.
+fn main50(headers: &[u8], offset: u64) -> Option<MainHeader> {
+    const MAIN: u64 = 1;
+    const FILE: u64 = 2;
+    const SERVICE: u64 = 3;
+    const ENCRYPTION: u64 = 4;
+    const END: u64 = 5;
+    const MHFL_VOLUME: u64 = 0x1;
+    const MHFL_VOLNUMBER: u64 = 0x2;
+    /// the compression version of RAR 7.0, `VER_PACK7` stored as in unrar
+    const VER_PACK7: u64 = 1;
+
+    let (main, mut next) = Block50::parse(headers)?;
+    if !main.intact {
+        return None;
+    }
+    match main.kind {
+        MAIN => {}
+        // the main header follows encrypted
+        ENCRYPTION => return Some(MainHeader::new(RarFormat::Rar5, offset, None)),
+        _ => return None,
+    }
+    let mut body = Reader(main.body);
+    let flags = body.vint()?;
+    let number = match flags & MHFL_VOLNUMBER {
+        0 => 0,
+        _ => body.vint()?,
+    };
+    // the locator's offsets are relative to the main header, which follows the signature
+    let position = offset + SIGNATURE.len() as u64 + 2;
+    let mut header = MainHeader::new(RarFormat::Rar5, offset, None);
+    main.extra(|kind, mut record| {
+        match kind {
+            1 => header.locator = locator(&mut record, position),
+            2 => (header.name, header.created) = metadata(&mut record),
+            _ => {}
+        }
+        Some(())
+    });
+
+    let mut first = number == 0;
+    while let Some((block, rest)) = next.and_then(Block50::parse) {
+        if !block.intact {
+            break;
+        }
+        match block.kind {
+            FILE | SERVICE => {
+                // like unrar, prefer what the first file says about being continued from a
+                // previous volume
+                first = !block.split_before();
+                if block.kind == FILE {
+                    if block.compression_version() == Some(VER_PACK7) {
+                        header.format.version = RarFormat::Rar7;
+                    }
+                    break;
+                }
+            }
+            END => break,
+            _ => {}
+        }
+        next = rest;
+    }
+    header.format.volume = Some(if flags & MHFL_VOLUME == 0 {
+        VolumeInfo::None
+    } else if first {
+        VolumeInfo::First
+    } else {
+        VolumeInfo::Subsequent
+    });
+    Some(header)
+}
+
+/// parses the locator record of a RAR 5.0 main header at `position`
+fn locator(record: &mut Reader, position: u64) -> Option<Locator> {
+    const MHEXTRA_LOCATOR_QLIST: u64 = 0x1;
+    const MHEXTRA_LOCATOR_RR: u64 = 0x2;
+
+    let flags = record.vint()?;
+    let mut offset = |flag| -> Option<Option<u64>> {
+        if flags & flag == 0 {
+            return Some(None);
+        }
+        // like unrar, treat a zero offset as missing
+        let offset = record.vint()?;
+        Some((offset != 0).then(|| position.saturating_add(offset)))
+    };
+    Some(Locator {
+        quick_open: offset(MHEXTRA_LOCATOR_QLIST)?,
+        recovery: offset(MHEXTRA_LOCATOR_RR)?,
+    })
+}
+
+/// parses the metadata record of a RAR 5.0 main header into the archive name and creation time
+fn metadata(record: &mut Reader) -> (Option<String>, Option<SystemTime>) {
+    const MHEXTRA_METADATA_NAME: u64 = 0x1;
+    const MHEXTRA_METADATA_CTIME: u64 = 0x2;
+    const MHEXTRA_METADATA_UNIXTIME: u64 = 0x4;
+    const MHEXTRA_METADATA_UNIX_NS: u64 = 0x8;
+
+    let Some(flags) = record.vint() else {
+        return (None, None);
+    };
+    let name = if flags & MHEXTRA_METADATA_NAME != 0 {
+        let name = record
+            .vint()
+            .and_then(|len| record.take(usize::try_from(len).ok()?));
+        let Some(name) = name else {
+            return (None, None);
+        };
+        // unrar accepts names with a trailing zero
+        let name = name.split(|&b| b == 0).next().unwrap_or_default();
+        Some(String::from_utf8_lossy(name).into_owned())
+    } else {
+        None
+    };
+    let created = if flags & MHEXTRA_METADATA_CTIME == 0 {
+        None
+    } else if flags & MHEXTRA_METADATA_UNIXTIME == 0 {
+        record.u64().and_then(filetime)
+    } else if flags & MHEXTRA_METADATA_UNIX_NS != 0 {
+        record
+            .u64()
+            .and_then(|ns| SystemTime::UNIX_EPOCH.checked_add(Duration::from_nanos(ns)))
+    } else {
+        record
+            .u32()
+            .and_then(|s| SystemTime::UNIX_EPOCH.checked_add(Duration::from_secs(s as u64)))
+    };
+    (name, created)
+}
+
+/// converts a Windows FILETIME, in 100 ns since 1601, to a `SystemTime`
+pub(crate) fn filetime(ticks: u64) -> Option<SystemTime> {
+    const UNIX_EPOCH_TICKS: u64 = 116_444_736_000_000_000;
+    let since = |ticks: u64| Duration::new(ticks / 10_000_000, (ticks % 10_000_000) as u32 * 100);
+    match ticks.checked_sub(UNIX_EPOCH_TICKS) {
+        Some(after) => SystemTime::UNIX_EPOCH.checked_add(since(after)),
+        None => SystemTime::UNIX_EPOCH.checked_sub(since(UNIX_EPOCH_TICKS - ticks)),
+    }
+}
+
+/// A header block of a RAR 1.5 to 4.x archive.
+pub(crate) struct Block15 {
+    pub(crate) kind: u8,
+    pub(crate) flags: u16,
+    /// the size of the header, without the data following it
+    pub(crate) size: usize,
+    /// the size of the data following the header
+    pub(crate) data_size: u64,
+    /// does the CRC match
+    pub(crate) intact: bool,
+}
+
+impl Block15 {
+    /// parses the block at the beginning of `bytes` and returns the bytes after it, if any
+    pub(crate) fn parse(bytes: &[u8]) -> Option<(Self, Option<&[u8]>)> {
+        const LONG_BLOCK: u16 = 0x8000;
+        const LHD_LARGE: u16 = 0x100;
+
+        let mut reader = Reader(bytes);
+        let crc = reader.u16()?;
+        let kind = reader.u8()?;
+        let flags = reader.u16()?;
+        let size = reader.u16()? as usize;
+        if size < 7 {
+            return None;
+        }
+        let header = bytes.get(2..size)?;
+        let intact = crc32fast::hash(header) as u16 == crc;
+        let mut body = Reader(&header[5..]);
+        // file and service headers always have a data size, which may have 64 bits
+        let data_size = match kind {
+            0x74 | 0x7a => {
+                let low = body.u32()? as u64;
+                let high = match flags & LHD_LARGE {
+                    0 => 0,
+                    _ => Reader(body.0.get(21..)?).u32()? as u64,
+                };
+                high << 32 | low
+            }
+            _ if flags & LONG_BLOCK != 0 => body.u32()? as u64,
+            _ => 0,
+        };
+        let next = usize::try_from(data_size)
+            .ok()
+            .and_then(|data_size| bytes.get(size.checked_add(data_size)?..));
+        Some((
+            Block15 {
+                kind,
+                flags,
+                size,
+                data_size,
+                intact,
+            },
+            next,
+        ))
+    }
+}
+
+/// A header block of a RAR 5.0 archive.
+pub(crate) struct Block50<'a> {
+    pub(crate) kind: u64,
+    pub(crate) flags: u64,
+    /// the type specific fields following the common ones
+    pub(crate) body: &'a [u8],
+    /// the extra area with the records following the type specific fields
+    extra: &'a [u8],
+    /// the size of the data following the header
+    pub(crate) data_size: u64,
+    /// does the CRC match
+    pub(crate) intact: bool,
+}
+
+impl<'a> Block50<'a> {
+    /// parses the block at the beginning of `bytes` and returns the bytes after it, if any
+    pub(crate) fn parse(bytes: &'a [u8]) -> Option<(Self, Option<&'a [u8]>)> {
+        const HFL_EXTRA: u64 = 0x1;
+        const HFL_DATA: u64 = 0x2;
+
+        let mut reader = Reader(bytes);
+        let crc = reader.u32()?;
+        let size = usize::try_from(reader.vint()?).ok()?;
+        let end = (bytes.len() - reader.0.len()).checked_add(size)?;
+        let header = reader.take(size)?;
+        let intact = crc32fast::hash(&bytes[4..end]) == crc;
+        let mut fields = Reader(header);
+        let kind = fields.vint()?;
+        let flags = fields.vint()?;
+        let extra_size = match flags & HFL_EXTRA {
+            0 => 0,
+            _ => usize::try_from(fields.vint()?).ok()?,
+        };
+        let data_size = match flags & HFL_DATA {
+            0 => 0,
+            _ => fields.vint()?,
+        };
+        let (body, extra) = fields.0.split_at(fields.0.len().checked_sub(extra_size)?);
+        let next = usize::try_from(data_size)
+            .ok()
+            .and_then(|data_size| bytes.get(end.checked_add(data_size)?..));
+        Some((
+            Block50 {
+                kind,
+                flags,
+                body,
+                extra,
+                data_size,
+                intact,
+            },
+            next,
+        ))
+    }
+
+    /// is this block continued from the previous volume
+    pub(crate) fn split_before(&self) -> bool {
+        const HFL_SPLITBEFORE: u64 = 0x8;
+        self.flags & HFL_SPLITBEFORE != 0
+    }
+
+    /// calls `f` with the type and data of every record in the extra area until it returns
+    /// `None`, which is also the case for malformed records
+    pub(crate) fn extra(&self, mut f: impl FnMut(u64, Reader<'a>) -> Option<()>) {
+        let mut extra = Reader(self.extra);
+        while !extra.0.is_empty() {
+            let Some(mut record) = extra
+                .vint()
+                .and_then(|size| extra.take(usize::try_from(size).ok()?))
+                .map(Reader)
+            else {
+                return;
+            };
+            if record.vint().and_then(|kind| f(kind, record)).is_none() {
+                return;
+            }
+        }
+    }
+
+    /// the subdata record of a service header, e.g. the name of an NTFS stream
+    pub(crate) fn subdata(&self) -> Option<&'a [u8]> {
+        const FHEXTRA_SUBDATA: u64 = 0x7;
+
+        let mut extra = Reader(self.extra);
+        while !extra.0.is_empty() {
+            let size = usize::try_from(extra.vint()?).ok()?;
+            let start = self.extra.len() - extra.0.len();
+            let mut record = Reader(extra.take(size)?);
+            if record.vint()? != FHEXTRA_SUBDATA {
+                continue;
+            }
+            // RAR 5.21 and older stored the size of this record, which is always the last
+            // one, off by one, so like unrar, take the single byte left over as well
+            let end = match extra.0.len() {
+                1 => self.extra.len(),
+                _ => start + size,
+            };
+            return Some(&self.extra[start + size - record.0.len()..end]);
+        }
+        None
+    }
+
+    /// the version of the compression algorithm of a file header, `0` for RAR 5.0 and `1`
+    /// for RAR 7.0
+    fn compression_version(&self) -> Option<u64> {
+        const FHFL_UTIME: u64 = 0x2;
+        const FHFL_CRC32: u64 = 0x4;
+
+        let mut body = Reader(self.body);
+        let flags = body.vint()?;
+        body.vint()?; // unpacked size
+        body.vint()?; // attributes
+        if flags & FHFL_UTIME != 0 {
+            body.u32()?;
+        }
+        if flags & FHFL_CRC32 != 0 {
+            body.u32()?;
+        }
+        Some(body.vint()? & 0x3f)
+    }
+}
+
+/// Reads little endian integers off the front of a byte slice.
+pub(crate) struct Reader<'a>(pub(crate) &'a [u8]);
+
+impl<'a> Reader<'a> {
+    pub(crate) fn take(&mut self, n: usize) -> Option<&'a [u8]> {
+        if n > self.0.len() {
+            return None;
+        }
+        let (taken, rest) = self.0.split_at(n);
+        self.0 = rest;
+        Some(taken)
+    }
+
+    pub(crate) fn u8(&mut self) -> Option<u8> {
+        Some(self.take(1)?[0])
+    }
+
+    pub(crate) fn u16(&mut self) -> Option<u16> {
+        Some(u16::from_le_bytes(self.take(2)?.try_into().ok()?))
+    }
+
+    pub(crate) fn u32(&mut self) -> Option<u32> {
+        Some(u32::from_le_bytes(self.take(4)?.try_into().ok()?))
+    }
+
+    pub(crate) fn u64(&mut self) -> Option<u64> {
+        Some(u64::from_le_bytes(self.take(8)?.try_into().ok()?))
+    }
+
+    /// reads a variable length integer of RAR 5.0: 7 bits per byte, the highest bit is set
+    /// in all bytes but the last
+    pub(crate) fn vint(&mut self) -> Option<u64> {
+        let mut value = 0;
+        for shift in (0..70).step_by(7) {
+            let byte = self.u8()?;
+            value |= ((byte & 0x7f) as u64).checked_shl(shift)?;
+            if byte & 0x80 == 0 {
+                return Some(value);
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Reader;
+
+    #[test]
+    fn vint() {
+        assert_eq!(Reader(&[0x05]).vint(), Some(5));
+        assert_eq!(Reader(&[0x80, 0x01]).vint(), Some(128));
+        assert_eq!(Reader(&[0xff; 11]).vint(), None);
+        assert_eq!(Reader(&[0x80]).vint(), None);
+    }
+}
@@ -0,0 +1,634 @@
+//! Mounting archives as read-only file systems, available with the `fuse` feature on Linux.
+//!
+//! The directory tree is built from a listing pass when the file system is created. Files
+//! stored as is are read straight from the volumes with
+//! [`OpenArchive::open_seekable`](crate::OpenArchive::open_seekable). Compressed files that
+//! fit into the cache of recently read files are decompressed as a whole and kept there, so
+//! reading them piece by piece, like the kernel does, only decompresses them once. Larger
+//! ones are never cached: every open file decompresses them on a thread of its own as they
+//! are read, starting over if the file is read backwards.
+//!
+//! The `unrar-fuse` binary mounts an archive from the command line:
+//!
+//! ```text
+//! unrar-fuse [--password <password>] <archive> <mountpoint>
+//! ```
+//!
+//! # Example
+//!
+//! ```no_run
+//! use unrar::{fuse::RarFs, Archive};
+//!
+//! let fs = RarFs::new(Archive::new("movies.part1.rar").as_first_part()).unwrap();
+//! // blocks until the file system is unmounted
+//! fs.mount("/mnt/movies").unwrap();
+//! ```
+
+use crate::error::{Code, UnrarError, UnrarResult};
+use crate::index::ArchiveIndex;
+use crate::open_archive::{FileHeader, Link, LinkKind};
+use crate::sink::Sink;
+use crate::stored::{self, StoredReader};
+use crate::{Archive, ExtractLimits, SecretPassword};
+use fuser::{
+    FileAttr, FileType, Filesystem, MountOption, ReplyAttr, ReplyData, ReplyDirectory, ReplyEmpty,
+    ReplyEntry, ReplyOpen, Request, FUSE_ROOT_ID,
+};
+use lru::LruCache;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
+use std::ffi::{OsStr, OsString};
+use std::io::{self, Read, Seek, SeekFrom};
+use std::os::raw::c_int;
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Component, Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::sync::Arc;
+use std::time::{Duration, UNIX_EPOCH};
+
+/// how long the kernel may cache attributes and lookups, which never change
+const TTL: Duration = Duration::from_secs(60 * 60);
+
+/// how many chunks a decoder may get ahead of the reads of its file
+const CHUNKS: usize = 4;
+
+/// how much data a decoder keeps before the offset read last, since the kernel may read a
+/// little out of order. This is the largest read the kernel asks for by default.
+const BEHIND: u64 = 128 << 10;
+
+/// A read-only FUSE file system serving the entries of an archive.
+///
+/// Symbolic links of RAR 4.x archives are left out, since unrar does not tell their
+/// target, and so are entries whose path leads out of the archive.
+pub struct RarFs {
+    filename: PathBuf,
+    password: Option<SecretPassword>,
+    limits: ExtractLimits,
+    index: Arc<ArchiveIndex>,
+    /// the nodes by their inode number minus one, starting with the root directory
+    nodes: Vec<Node>,
+    cache: Cache,
+    /// the open files by their handle
+    files: HashMap<u64, Reader>,
+    next_handle: u64,
+    uid: u32,
+    gid: u32,
+}
+
+struct Node {
+    parent: u64,
+    /// the offset of the entry in the index, `None` for directories only implied by the
+    /// paths of other entries
+    entry: Option<usize>,
+    kind: NodeKind,
+}
+
+enum NodeKind {
+    Directory(BTreeMap<OsString, u64>),
+    /// a file with the data of the entry called so, which is another one for hard links and
+    /// file copies
+    File(PathBuf),
+    Symlink(PathBuf),
+}
+
+/// the data of recently read files no larger than the capacity, by their inode number
+struct Cache {
+    files: LruCache<u64, Vec<u8>>,
+    size: usize,
+    capacity: usize,
+}
+
+/// where an open file reads its data from
+enum Reader {
+    /// the whole data of the entry called so, kept in the cache
+    Cached(PathBuf),
+    /// the volumes, for files stored as is
+    Stored(StoredReader),
+    /// a thread decompressing the entry called so
+    Decoder(PathBuf, Decoder),
+}
+
+/// The data of an entry as it is decompressed on a thread of its own, which is aborted once
+/// this is dropped.
+struct Decoder {
+    chunks: Receiver<UnrarResult<Vec<u8>>>,
+    /// the data received last, which starts at `start`
+    buffer: Vec<u8>,
+    start: u64,
+    /// the error number the decompression failed with, if any
+    failed: Option<c_int>,
+    done: bool,
+}
+
+/// hands the chunks of data decompressed to a [`Decoder`]
+struct ChunkSink(SyncSender<UnrarResult<Vec<u8>>>);
+
+/// A mounted [`RarFs`], which is unmounted when dropped.
+pub struct Mount {
+    _session: fuser::BackgroundSession,
+}
+
+impl RarFs {
+    /// lists `archive` to build the directory tree, with a cache of 256 MiB.
+    ///
+    /// Start from the first part of multipart archives, see
+    /// [`Archive::as_first_part`](crate::Archive::as_first_part).
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of listing the archive, like [`Archive::index`] does.
+    pub fn new(archive: Archive) -> UnrarResult<Self> {
+        let index = archive.index()?;
+        let root = Node {
+            parent: FUSE_ROOT_ID,
+            entry: None,
+            kind: NodeKind::Directory(BTreeMap::new()),
+        };
+        let mut fs = RarFs {
+            filename: archive.filename().to_path_buf(),
+            password: archive.password().cloned(),
+            limits: archive.limits(),
+            index: Arc::new(index),
+            nodes: vec![root],
+            cache: Cache {
+                files: LruCache::unbounded(),
+                size: 0,
+                capacity: 256 << 20,
+            },
+            files: HashMap::new(),
+            next_handle: 0,
+            uid: unsafe { libc::getuid() },
+            gid: unsafe { libc::getgid() },
+        };
+        for offset in 0..fs.index.len() {
+            let entry = &fs.index.entries()[offset];
+            let kind = match entry.link() {
+                _ if entry.is_directory() => NodeKind::Directory(BTreeMap::new()),
+                None => NodeKind::File(entry.filename.clone()),
+                Some(link) => match link.kind {
+                    LinkKind::Hardlink | LinkKind::FileCopy => NodeKind::File(link.target.clone()),
+                    _ if link.target.as_os_str().is_empty() => continue,
+                    _ => NodeKind::Symlink(link.target.clone()),
+                },
+            };
+            let path = entry.filename.clone();
+            fs.insert(&path, offset, kind);
+        }
+        Ok(fs)
+    }
+
+    /// sets the size of the cache of recently read files in bytes. Compressed files larger
+    /// than that are decompressed as they are read instead.
+    pub fn with_cache_size(mut self, bytes: usize) -> Self {
+        self.cache.capacity = bytes;
+        self
+    }
+
+    /// mounts the file system at `mountpoint` and serves it until it is unmounted
+    pub fn mount<P: AsRef<Path>>(self, mountpoint: P) -> io::Result<()> {
+        let options = self.options();
+        fuser::mount2(self, mountpoint, &options)
+    }
+
+    /// mounts the file system at `mountpoint` and serves it from a background thread until
+    /// the returned [`Mount`] is dropped
+    pub fn spawn_mount<P: AsRef<Path>>(self, mountpoint: P) -> io::Result<Mount> {
+        let options = self.options();
+        let session = fuser::spawn_mount2(self, mountpoint, &options)?;
+        Ok(Mount { _session: session })
+    }
+
+    fn options(&self) -> Vec<MountOption> {
+        let name = self
+            .filename
+            .file_name()
+            .unwrap_or(self.filename.as_os_str());
+        vec![
+            MountOption::RO,
+            MountOption::FSName(name.to_string_lossy().into_owned()),
+            MountOption::Subtype("rar".to_owned()),
+        ]
+    }
+
+    /// adds the entry at `offset` as the node `path`, creating the directories it is in
+    fn insert(&mut self, path: &Path, offset: usize, kind: NodeKind) {
+        let mut names = Vec::new();
+        for component in path.components() {
+            match component {
+                Component::Normal(name) => names.push(name),
+                Component::CurDir => {}
+                _ => return,
+            }
+        }
+        let Some((name, parents)) = names.split_last() else {
+            return;
+        };
+        let mut parent = FUSE_ROOT_ID;
+        for name in parents {
+            parent = match self.child(parent, name) {
+                Some(ino) => ino,
+                None => self.add(parent, name, None, NodeKind::Directory(BTreeMap::new())),
+            };
+            if !matches!(self.node(parent).kind, NodeKind::Directory(_)) {
+                return;
+            }
+        }
+        match self.child(parent, name) {
+            // like the index, the first entry of a name wins
+            Some(ino) => {
+                let node = &mut self.nodes[ino as usize - 1];
+                if node.entry.is_none() && matches!(kind, NodeKind::Directory(_)) {
+                    node.entry = Some(offset);
+                }
+            }
+            None => {
+                self.add(parent, name, Some(offset), kind);
+            }
+        }
+    }
+
+    fn add(&mut self, parent: u64, name: &OsStr, entry: Option<usize>, kind: NodeKind) -> u64 {
+        self.nodes.push(Node {
+            parent,
+            entry,
+            kind,
+        });
+        let ino = self.nodes.len() as u64;
+        if let NodeKind::Directory(children) = &mut self.nodes[parent as usize - 1].kind {
+            children.insert(name.to_os_string(), ino);
+        }
+        ino
+    }
+
+    fn node(&self, ino: u64) -> &Node {
+        &self.nodes[ino as usize - 1]
+    }
+
+    fn get(&self, ino: u64) -> Option<&Node> {
+        self.nodes.get(usize::try_from(ino).ok()?.checked_sub(1)?)
+    }
+
+    fn child(&self, parent: u64, name: &OsStr) -> Option<u64> {
+        match &self.get(parent)?.kind {
+            NodeKind::Directory(children) => children.get(name).copied(),
+            _ => None,
+        }
+    }
+
+    fn header(&self, node: &Node) -> Option<&FileHeader> {
+        node.entry.map(|offset| &self.index.entries()[offset])
+    }
+
+    fn attr(&self, ino: u64) -> Option<FileAttr> {
+        let node = self.get(ino)?;
+        let header = self.header(node);
+        let (kind, size, perm) = match &node.kind {
+            NodeKind::Directory(_) => (FileType::Directory, 0, 0o755),
+            NodeKind::File(source) => {
+                let size = self.index.get(source).map_or(0, |h| h.unpacked_size);
+                (FileType::RegularFile, size, 0o644)
+            }
+            NodeKind::Symlink(target) => {
+                let size = target.as_os_str().len() as u64;
+                (FileType::Symlink, size, 0o777)
+            }
+        };
+        let mtime = header.and_then(FileHeader::modified).unwrap_or(UNIX_EPOCH);
+        Some(FileAttr {
+            ino,
+            size,
+            blocks: size.div_ceil(512),
+            atime: mtime,
+            mtime,
+            ctime: mtime,
+            crtime: mtime,
+            kind,
+            perm: header.map_or(perm, FileHeader::permissions) as u16,
+            nlink: 1,
+            uid: self.uid,
+            gid: self.gid,
+            rdev: 0,
+            blksize: 512,
+            flags: 0,
+        })
+    }
+
+    /// opens the file `ino`, choosing how to read it
+    fn open_file(&self, ino: u64) -> UnrarResult<Option<Reader>> {
+        let Some(NodeKind::File(source)) = self.get(ino).map(|node| &node.kind) else {
+            return Ok(None);
+        };
+        let Some(header) = self.index.get(source) else {
+            return Ok(None);
+        };
+        let reader = if stored::is_stored(header) {
+            let archive = archive(&self.filename, self.password.as_ref(), self.limits);
+            match archive.open_for_listing()?.open_seekable(header)? {
+                Some(reader) => Reader::Stored(reader),
+                None => return Ok(None),
+            }
+        } else if header.unpacked_size <= self.cache.capacity as u64 {
+            Reader::Cached(source.clone())
+        } else {
+            Reader::Decoder(source.clone(), self.decoder(source))
+        };
+        Ok(Some(reader))
+    }
+
+    /// starts decompressing the entry called `source` on a thread of its own
+    fn decoder(&self, source: &Path) -> Decoder {
+        let (sender, chunks) = mpsc::sync_channel(CHUNKS);
+        let filename = self.filename.clone();
+        let password = self.password.clone();
+        let limits = self.limits;
+        let index = Arc::clone(&self.index);
+        let source = source.to_path_buf();
+        std::thread::spawn(move || {
+            let archive = archive(&filename, password.as_ref(), limits);
+            let mut sink = ChunkSink(sender.clone());
+            let result = archive
+                .open_entry(&index, &source)
+                .and_then(|entry| entry.map(|entry| entry.read_to_sink(&mut sink)).transpose());
+            if let Err(error) = result {
+                // nobody listens anymore if the file was closed
+                let _ = sender.send(Err(error));
+            }
+        });
+        Decoder {
+            chunks,
+            buffer: Vec::new(),
+            start: 0,
+            failed: None,
+            done: false,
+        }
+    }
+
+    /// reads at most `size` bytes at `offset` of the open file `handle` of the file `ino`
+    fn read_file(
+        &mut self,
+        ino: u64,
+        handle: u64,
+        offset: u64,
+        size: usize,
+    ) -> Result<Cow<'_, [u8]>, c_int> {
+        let mut restart = None;
+        match self.files.get(&handle) {
+            Some(Reader::Cached(source)) => {
+                let source = source.clone();
+                let data = match self.whole(ino, &source) {
+                    Ok(Some(data)) => data,
+                    Ok(None) => return Err(libc::ENOENT),
+                    Err(error) => return Err(errno(&error)),
+                };
+                let start = usize::try_from(offset)
+                    .unwrap_or(usize::MAX)
+                    .min(data.len());
+                let end = start.saturating_add(size).min(data.len());
+                return Ok(match data {
+                    Cow::Borrowed(data) => Cow::Borrowed(&data[start..end]),
+                    Cow::Owned(data) => Cow::Owned(data[start..end].to_vec()),
+                });
+            }
+            // the data before `offset` is gone, so the decoder has to start over
+            Some(Reader::Decoder(source, decoder)) if offset < decoder.start => {
+                restart = Some(self.decoder(source));
+            }
+            _ => {}
+        }
+        let Some(reader) = self.files.get_mut(&handle) else {
+            return Err(libc::EBADF);
+        };
+        match reader {
+            Reader::Cached(_) => unreachable!("cached files were read above"),
+            Reader::Stored(reader) => {
+                let mut data = Vec::with_capacity(size);
+                reader
+                    .seek(SeekFrom::Start(offset))
+                    .map_err(|_| libc::EIO)?;
+                reader
+                    .take(size as u64)
+                    .read_to_end(&mut data)
+                    .map_err(|_| libc::EIO)?;
+                Ok(Cow::Owned(data))
+            }
+            Reader::Decoder(_, decoder) => {
+                if let Some(restart) = restart {
+                    *decoder = restart;
+                }
+                decoder.read(offset, size).map(Cow::Borrowed)
+            }
+        }
+    }
+
+    /// the whole data of the entry called `source` for the file `ino`, from the cache or
+    /// decompressed and added to it
+    fn whole(&mut self, ino: u64, source: &Path) -> UnrarResult<Option<Cow<'_, [u8]>>> {
+        if self.cache.files.contains(&ino) {
+            return Ok(self
+                .cache
+                .files
+                .get(&ino)
+                .map(|data| Cow::Borrowed(&data[..])));
+        }
+        let archive = archive(&self.filename, self.password.as_ref(), self.limits);
+        let Some(entry) = archive.open_entry(&self.index, source)? else {
+            return Ok(None);
+        };
+        let (data, _) = entry.read()?;
+        Ok(Some(self.cache.insert(ino, data)))
+    }
+}
+
+/// the archive at `filename` with `password` and `limits`
+fn archive<'a>(
+    filename: &'a Path,
+    password: Option<&SecretPassword>,
+    limits: ExtractLimits,
+) -> Archive<'a> {
+    let archive = match password {
+        Some(password) => Archive::with_secret_password(filename, password.clone()),
+        None => Archive::new(filename),
+    };
+    archive.with_limits(limits)
+}
+
+impl Cache {
+    /// adds `data`, evicting the least recently read files to make room for it. Data larger
+    /// than the whole cache is handed back without being added.
+    fn insert(&mut self, ino: u64, data: Vec<u8>) -> Cow<'_, [u8]> {
+        if data.len() > self.capacity {
+            return Cow::Owned(data);
+        }
+        while self.size + data.len() > self.capacity {
+            match self.files.pop_lru() {
+                Some((_, evicted)) => self.size -= evicted.len(),
+                None => break,
+            }
+        }
+        self.size += data.len();
+        Cow::Borrowed(self.files.get_or_insert(ino, || data))
+    }
+}
+
+impl Decoder {
+    /// at most `size` bytes of the data at `offset`, which must not be before `start`,
+    /// waiting for the thread to decompress that far
+    fn read(&mut self, offset: u64, size: usize) -> Result<&[u8], c_int> {
+        let end = offset.saturating_add(size as u64);
+        while !self.done && self.start + (self.buffer.len() as u64) < end {
+            // drop what lies well before `offset`, it is not read again unless seeking back
+            let before = offset.saturating_sub(BEHIND).saturating_sub(self.start);
+            let before = before.min(self.buffer.len() as u64) as usize;
+            self.buffer.drain(..before);
+            self.start += before as u64;
+            match self.chunks.recv() {
+                Ok(Ok(chunk)) => self.buffer.extend_from_slice(&chunk),
+                Ok(Err(error)) => {
+                    self.failed = Some(errno(&error));
+                    self.done = true;
+                }
+                // the thread is done
+                Err(_) => self.done = true,
+            }
+        }
+        let len = self.buffer.len();
+        let from = (offset - self.start).min(len as u64) as usize;
+        let to = (end - self.start).min(len as u64) as usize;
+        match self.failed {
+            // the data ends early because of the error
+            Some(errno) if to - from < size => Err(errno),
+            _ => Ok(&self.buffer[from..to]),
+        }
+    }
+}
+
+impl Sink for ChunkSink {
+    fn create_dir(&mut self, _: &FileHeader) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn begin_file(&mut self, _: &FileHeader) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn write_chunk(&mut self, data: &[u8]) -> io::Result<()> {
+        // fails once the decoder is dropped, which aborts decompressing
+        self.0
+            .send(Ok(data.to_vec()))
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "the file was closed"))
+    }
+
+    fn end_file(&mut self, _: &FileHeader) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn create_link(&mut self, _: &FileHeader, _: &Link) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// the error number for `error`
+fn errno(error: &UnrarError) -> c_int {
+    match error.code {
+        Code::MissingPassword | Code::BadPassword => libc::EACCES,
+        Code::NoMemory | Code::LimitExceeded => libc::ENOMEM,
+        _ => libc::EIO,
+    }
+}
+
+impl Filesystem for RarFs {
+    fn lookup(&mut self, _: &Request, parent: u64, name: &OsStr, reply: ReplyEntry) {
+        match self.child(parent, name).and_then(|ino| self.attr(ino)) {
+            Some(attr) => reply.entry(&TTL, &attr, 0),
+            None => reply.error(libc::ENOENT),
+        }
+    }
+
+    fn getattr(&mut self, _: &Request, ino: u64, _: Option<u64>, reply: ReplyAttr) {
+        match self.attr(ino) {
+            Some(attr) => reply.attr(&TTL, &attr),
+            None => reply.error(libc::ENOENT),
+        }
+    }
+
+    fn readlink(&mut self, _: &Request, ino: u64, reply: ReplyData) {
+        match self.get(ino).map(|node| &node.kind) {
+            Some(NodeKind::Symlink(target)) => reply.data(target.as_os_str().as_bytes()),
+            Some(_) => reply.error(libc::EINVAL),
+            None => reply.error(libc::ENOENT),
+        }
+    }
+
+    fn open(&mut self, _: &Request, ino: u64, _: i32, reply: ReplyOpen) {
+        match self.open_file(ino) {
+            Ok(Some(reader)) => {
+                let handle = self.next_handle;
+                self.next_handle += 1;
+                self.files.insert(handle, reader);
+                reply.opened(handle, 0);
+            }
+            Ok(None) => reply.error(libc::ENOENT),
+            Err(error) => reply.error(errno(&error)),
+        }
+    }
+
+    fn read(
+        &mut self,
+        _: &Request,
+        ino: u64,
+        fh: u64,
+        offset: i64,
+        size: u32,
+        _: i32,
+        _: Option<u64>,
+        reply: ReplyData,
+    ) {
+        match self.read_file(ino, fh, offset.max(0) as u64, size as usize) {
+            Ok(data) => reply.data(&data),
+            Err(errno) => reply.error(errno),
+        }
+    }
+
+    fn release(
+        &mut self,
+        _: &Request,
+        _: u64,
+        fh: u64,
+        _: i32,
+        _: Option<u64>,
+        _: bool,
+        reply: ReplyEmpty,
+    ) {
+        self.files.remove(&fh);
+        reply.ok();
+    }
+
+    fn readdir(&mut self, _: &Request, ino: u64, _: u64, offset: i64, mut reply: ReplyDirectory) {
+        let Some(node) = self.get(ino) else {
+            return reply.error(libc::ENOENT);
+        };
+        let NodeKind::Directory(children) = &node.kind else {
+            return reply.error(libc::ENOTDIR);
+        };
+        let dots = [
+            (ino, FileType::Directory, OsStr::new(".")),
+            (node.parent, FileType::Directory, OsStr::new("..")),
+        ];
+        let children = children.iter().map(|(name, &child)| {
+            let kind = match self.node(child).kind {
+                NodeKind::Directory(_) => FileType::Directory,
+                NodeKind::File(_) => FileType::RegularFile,
+                NodeKind::Symlink(_) => FileType::Symlink,
+            };
+            (child, kind, name.as_os_str())
+        });
+        let entries = dots.into_iter().chain(children).enumerate();
+        for (i, (ino, kind, name)) in entries.skip(offset.max(0) as usize) {
+            // the offset of an entry is that of the next one to read
+            if reply.add(ino, i as i64 + 1, kind, name) {
+                break;
+            }
+        }
+        reply.ok();
+    }
+}
@@ -0,0 +1,136 @@
+use crate::error::{Code, UnrarError, UnrarResult, When};
+use crate::open_archive::{
+    CursorBeforeFile, CursorBeforeHeader, FileHeader, List, OpenArchive, Process,
+};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// An index over the entries of an archive, built once from a listing pass.
+///
+/// The index remembers the offset of every entry, that is, how many entries precede it in
+/// the archive. [`Archive::open_entry`](crate::Archive::open_entry) uses it to find an entry
+/// by counting the headers on the way instead of comparing their names. This is no random
+/// access: the unrar library cannot seek to a header, so all headers before the entry are
+/// still read, and opening an entry stays linear in the number of entries preceding it.
+///
+/// Create one with [`Archive::index`](crate::Archive::index).
+///
+/// # Example
+///
+/// ```
+/// use unrar::Archive;
+///
+/// let index = Archive::new("data/multi.rar").index().unwrap();
+/// assert_eq!(index.len(), 5);
+/// assert_eq!(index.offset("src/util.rs"), Some(3));
+/// assert!(index.get("src").unwrap().is_directory());
+/// ```
+#[derive(Debug)]
+pub struct ArchiveIndex {
+    solid: bool,
+    entries: Vec<FileHeader>,
+    offsets: HashMap<PathBuf, usize>,
+}
+
+impl ArchiveIndex {
+    pub(crate) fn new(archive: OpenArchive<List, CursorBeforeHeader>) -> UnrarResult<Self> {
+        let solid = archive.is_solid();
+        let entries = archive.collect::<UnrarResult<Vec<_>>>()?;
+        let mut offsets = HashMap::with_capacity(entries.len());
+        for (offset, entry) in entries.iter().enumerate() {
+            // an archive may contain the same name more than once, the first one wins
+            offsets.entry(entry.filename.clone()).or_insert(offset);
+        }
+        Ok(ArchiveIndex {
+            solid,
+            entries,
+            offsets,
+        })
+    }
+
+    /// the entries of the archive in the order they appear in
+    pub fn entries(&self) -> &[FileHeader] {
+        &self.entries
+    }
+
+    /// the number of entries in the archive
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// does the archive have no entries at all
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// was the index built from a solid archive.
+    ///
+    /// Entries of solid archives cannot be reached without decompressing the ones before,
+    /// which is why [`Archive::open_entry`](crate::Archive::open_entry) looks for them by
+    /// name while decompressing everything on the way.
+    pub fn is_solid(&self) -> bool {
+        self.solid
+    }
+
+    /// returns the offset of the entry called `name`, i.e. the number of entries preceding it.
+    pub fn offset<P: AsRef<Path>>(&self, name: P) -> Option<usize> {
+        self.offsets.get(name.as_ref()).copied()
+    }
+
+    /// returns the header of the entry called `name`
+    pub fn get<P: AsRef<Path>>(&self, name: P) -> Option<&FileHeader> {
+        self.offset(name).map(|offset| &self.entries[offset])
+    }
+
+    /// Advances `archive` to the entry called `name`.
+    ///
+    /// Fails with [`Code::BadArchive`] if the entry found for `name` does not match the one
+    /// in the index, i.e. the index was built from another archive or the archive changed
+    /// since.
+    pub(crate) fn seek(
+        &self,
+        archive: OpenArchive<Process, CursorBeforeHeader>,
+        name: &Path,
+    ) -> UnrarResult<Option<OpenArchive<Process, CursorBeforeFile>>> {
+        let stale = || UnrarError::from(Code::BadArchive, When::Read).with_filename(name);
+        if archive.is_solid() != self.solid {
+            return Err(stale());
+        }
+        let Some(offset) = self.offset(name) else {
+            return Ok(None);
+        };
+        let indexed = &self.entries[offset];
+        let found = match self.solid {
+            true => scan(archive, |_, entry| entry.filename == name)?,
+            false => scan(archive, |position, _| position == offset)?,
+        };
+        match found {
+            Some(found) if found.entry().is_same_entry(indexed) => Ok(Some(found)),
+            _ => Err(stale()),
+        }
+    }
+}
+
+/// reads headers until `found` returns true, skipping over the payload of all others.
+///
+/// `found` receives the offset of the entry as it was counted in [`List`] mode: continuation
+/// headers of split entries show up in [`Process`] mode, but not when listing, so they are
+/// skipped without being counted.
+fn scan<F: Fn(usize, &FileHeader) -> bool>(
+    mut archive: OpenArchive<Process, CursorBeforeHeader>,
+    found: F,
+) -> UnrarResult<Option<OpenArchive<Process, CursorBeforeFile>>> {
+    let mut position = 0;
+    while let Some(header) = archive.read_header()? {
+        if header.entry().is_split_before() {
+            archive = header.skip()?;
+            continue;
+        }
+        if found(position, header.entry()) {
+            return Ok(Some(header));
+        }
+        position += 1;
+        archive = header.skip()?;
+    }
+    Ok(None)
+}
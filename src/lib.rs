@@ -1,14 +1,41 @@
 #![doc = include_str!("../README.md")]
 #![warn(missing_docs)]
 
-pub use archive::Archive;
+pub use archive::{Archive, OpenOutcome};
+#[cfg(feature = "encoding")]
+pub use encoding_rs;
+pub use format::{detect, AuthenticityInfo, Format, Input, Locator, RarFormat};
+pub use index::ArchiveIndex;
+pub use limits::{ExtractLimits, Limit};
+pub use memory::{MemoryEntry, MemoryNode, MemoryTree};
+pub use password::SecretPassword;
+pub use services::ListOptions;
+pub use report::TestReport;
+pub use sink::{FsSink, MemorySink, Sink};
+#[cfg(target_os = "linux")]
+pub use services::StreamTarget;
+pub use stored::StoredReader;
 use unrar_sys as native;
 mod archive;
+#[cfg(feature = "convert")]
+pub mod convert;
 pub mod error;
+mod format;
+#[cfg(all(feature = "fuse", target_os = "linux"))]
+pub mod fuse;
+mod index;
+mod limits;
+mod memory;
+mod parallel;
+mod password;
 mod pathed;
+mod report;
+mod services;
+mod sink;
+mod stored;
 mod open_archive;
 pub use error::UnrarResult;
 pub use open_archive::{
-    CursorBeforeFile, CursorBeforeHeader, FileHeader, List, ListSplit, OpenArchive, Process,
-    VolumeInfo,
+    CursorBeforeFile, CursorBeforeHeader, EntryKind, FileHeader, Link, LinkKind, List, ListSplit,
+    Method, OpenArchive, Process, VolumeInfo,
 };
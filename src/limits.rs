@@ -0,0 +1,218 @@
+use crate::open_archive::FileHeader;
+use crate::services;
+use std::fmt;
+use std::path::{Component, Path};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// Limits on what an archive may extract, to reject decompression bombs safely.
+///
+/// An archive can declare a small size for an entry and then decompress to much more, or hold
+/// millions of tiny entries. Limits set on an [`Archive`](crate::Archive) with
+/// [`with_limits`](crate::Archive::with_limits) apply to every archive opened from it:
+///
+///    - the entry count, path depth and declared size are checked whenever a header is read
+///    - sizes and the compression ratio are checked while the data is decompressed,
+///      which aborts the operation as soon as a limit is exceeded
+///
+/// Either way, the operation fails with [`Code::LimitExceeded`](crate::error::Code::LimitExceeded)
+/// and [`UnrarError::limit`](crate::error::UnrarError::limit) tells which limit it was. Note
+/// that a file being extracted when a limit is exceeded is left behind partially written.
+///
+/// All limits are unset by default.
+///
+/// # Example
+///
+/// ```
+/// use unrar::{Archive, ExtractLimits, Limit};
+///
+/// let limits = ExtractLimits::new().max_total_size(4).max_path_depth(8);
+/// let archive = Archive::new("data/version.rar").with_limits(limits);
+/// let header = archive.open_for_processing().unwrap().read_header().unwrap().unwrap();
+/// let error = header.read().unwrap_err();
+/// assert_eq!(error.limit(), Some(Limit::TotalSize));
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ExtractLimits {
+    total_size: Option<u64>,
+    entry_size: Option<u64>,
+    entries: Option<usize>,
+    ratio: Option<u64>,
+    path_depth: Option<usize>,
+}
+
+impl ExtractLimits {
+    /// creates limits that do not limit anything yet
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// limits the number of bytes decompressed from all entries together.
+    ///
+    /// When extracting in parallel, this is the total of all threads.
+    pub fn max_total_size(mut self, bytes: u64) -> Self {
+        self.total_size = Some(bytes);
+        self
+    }
+
+    /// limits the number of bytes decompressed from a single entry
+    pub fn max_entry_size(mut self, bytes: u64) -> Self {
+        self.entry_size = Some(bytes);
+        self
+    }
+
+    /// limits the number of entries in the archive
+    pub fn max_entries(mut self, entries: usize) -> Self {
+        self.entries = Some(entries);
+        self
+    }
+
+    /// limits the ratio of decompressed bytes to compressed bytes of an entry.
+    ///
+    /// For entries split across volumes, whose packed size is only known per volume, the
+    /// compressed bytes are those of the volumes reached so far.
+    pub fn max_ratio(mut self, ratio: u64) -> Self {
+        self.ratio = Some(ratio);
+        self
+    }
+
+    /// limits the number of components of entry paths, e.g. `a/b/c.txt` has a depth of 3.
+    ///
+    /// `..` counts as a component as well, so `a/../../b` has a depth of 4.
+    pub fn max_path_depth(mut self, depth: usize) -> Self {
+        self.path_depth = Some(depth);
+        self
+    }
+}
+
+/// The limit of [`ExtractLimits`] that was exceeded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Limit {
+    /// [`ExtractLimits::max_total_size`]
+    TotalSize,
+    /// [`ExtractLimits::max_entry_size`]
+    EntrySize,
+    /// [`ExtractLimits::max_entries`]
+    Entries,
+    /// [`ExtractLimits::max_ratio`]
+    Ratio,
+    /// [`ExtractLimits::max_path_depth`]
+    PathDepth,
+}
+
+impl fmt::Display for Limit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Limit::TotalSize => write!(f, "total size limit"),
+            Limit::EntrySize => write!(f, "entry size limit"),
+            Limit::Entries => write!(f, "entry count limit"),
+            Limit::Ratio => write!(f, "compression ratio limit"),
+            Limit::PathDepth => write!(f, "path depth limit"),
+        }
+    }
+}
+
+/// Keeps track of an open archive against its [`ExtractLimits`].
+///
+/// Clones share the total of decompressed bytes, which is how parallel workers are limited
+/// together, but count entries on their own since every worker reads all headers.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct Budget {
+    limits: ExtractLimits,
+    entries: usize,
+    total: Arc<AtomicU64>,
+}
+
+impl Budget {
+    pub(crate) fn new(limits: ExtractLimits) -> Self {
+        Budget {
+            limits,
+            ..Default::default()
+        }
+    }
+
+    /// checks a header that has just been read
+    pub(crate) fn check_header(&mut self, header: &FileHeader) -> Result<(), Limit> {
+        // continuation headers of split entries do not count as entries of their own
+        if !header.is_split_before() {
+            self.entries += 1;
+        }
+        if self.limits.entries.is_some_and(|max| self.entries > max) {
+            return Err(Limit::Entries);
+        }
+        let depth = header
+            .filename
+            .components()
+            .filter(|c| matches!(c, Component::Normal(_) | Component::ParentDir))
+            .count();
+        if self.limits.path_depth.is_some_and(|max| depth > max) {
+            return Err(Limit::PathDepth);
+        }
+        if self
+            .limits
+            .entry_size
+            .is_some_and(|max| header.unpacked_size > max)
+        {
+            return Err(Limit::EntrySize);
+        }
+        Ok(())
+    }
+
+    /// starts keeping track of the data decompressed from `header`
+    pub(crate) fn entry(&self, header: &FileHeader) -> EntryBudget {
+        EntryBudget {
+            limits: self.limits,
+            total: Arc::clone(&self.total),
+            written: 0,
+            packed: Some(header.packed_size),
+            exceeded: None,
+        }
+    }
+}
+
+/// Keeps track of the data decompressed from a single entry, from within the callback.
+#[derive(Debug)]
+pub(crate) struct EntryBudget {
+    limits: ExtractLimits,
+    total: Arc<AtomicU64>,
+    written: u64,
+    /// the packed size of the entry, which is that of the volumes reached so far for split
+    /// entries, or `None` once the size of a part cannot be read
+    packed: Option<u64>,
+    /// the limit that was exceeded, after which the DLL call is aborted
+    pub(crate) exceeded: Option<Limit>,
+}
+
+impl EntryBudget {
+    /// accounts for the part of a split entry in `volume`, which the DLL just moved on to
+    pub(crate) fn next_volume(&mut self, volume: &Path) {
+        if self.limits.ratio.is_some() {
+            self.packed = self
+                .packed
+                .zip(services::continued_size(volume))
+                .map(|(packed, part)| packed.saturating_add(part));
+        }
+    }
+
+    /// accounts for `len` more bytes, returns false if that exceeds a limit
+    pub(crate) fn write(&mut self, len: usize) -> bool {
+        let len = len as u64;
+        self.written += len;
+        let total = self.total.fetch_add(len, Ordering::Relaxed) + len;
+        self.exceeded = if self.limits.total_size.is_some_and(|max| total > max) {
+            Some(Limit::TotalSize)
+        } else if self.limits.entry_size.is_some_and(|max| self.written > max) {
+            Some(Limit::EntrySize)
+        } else if self
+            .limits
+            .ratio
+            .zip(self.packed)
+            .is_some_and(|(ratio, packed)| self.written > packed.max(1).saturating_mul(ratio))
+        {
+            Some(Limit::Ratio)
+        } else {
+            None
+        };
+        self.exceeded.is_none()
+    }
+}
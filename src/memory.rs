@@ -0,0 +1,119 @@
+use crate::open_archive::{FileHeader, Link};
+use std::collections::BTreeMap;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+/// The entries of an archive extracted into memory, with their contents and metadata.
+///
+/// Entries are kept by their path in the archive, so the tree can be browsed like a file
+/// system without touching the disk. Paths are normalized like [`FsSink`](crate::FsSink)
+/// resolves them, so `a/./b`, `a//b` and `a/c/../b` all stand for `a/b`. Create one with
+/// [`Archive::extract_all_to_memory`](crate::Archive::extract_all_to_memory).
+///
+/// # Example
+///
+/// ```
+/// use unrar::{Archive, MemoryNode};
+///
+/// let tree = Archive::new("data/multi.rar").extract_all_to_memory().unwrap();
+/// assert_eq!(tree.len(), 5);
+/// assert_eq!(tree.read("VERSION"), Some(&b"unrar-0.5.8"[..]));
+/// let children: Vec<_> = tree.children("src").map(|(path, _)| path).collect();
+/// assert_eq!(children, ["src/lib.rs", "src/util.rs"]);
+/// assert!(matches!(tree.get("src").unwrap().node, MemoryNode::Directory));
+/// ```
+#[derive(Debug, Default)]
+pub struct MemoryTree {
+    entries: BTreeMap<PathBuf, MemoryEntry>,
+}
+
+/// An entry of a [`MemoryTree`].
+#[derive(Debug)]
+pub struct MemoryEntry {
+    /// the header of the entry, holding its metadata like the modification time
+    pub header: FileHeader,
+    #[allow(missing_docs)]
+    pub node: MemoryNode,
+}
+
+/// What a [`MemoryEntry`] is, along with its contents.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MemoryNode {
+    /// a directory, whose entries are found with [`MemoryTree::children`]
+    Directory,
+    /// a file with its contents
+    File(Vec<u8>),
+    /// a link, which has no contents of its own
+    Link(Link),
+}
+
+impl MemoryTree {
+    /// adds `entry`, replacing an earlier entry with the same path like extracting to disk
+    /// would. Fails if the path of `entry` leads out of the tree.
+    pub(crate) fn insert(&mut self, entry: MemoryEntry) -> io::Result<()> {
+        let name = &entry.header.filename;
+        let path = normalize(name).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{} leads out of the archive", name.display()),
+            )
+        })?;
+        self.entries.insert(path, entry);
+        Ok(())
+    }
+
+    /// the entry at `path`
+    pub fn get<P: AsRef<Path>>(&self, path: P) -> Option<&MemoryEntry> {
+        self.entries.get(&normalize(path.as_ref())?)
+    }
+
+    /// the contents of the file at `path`, `None` if there is no file at `path`
+    pub fn read<P: AsRef<Path>>(&self, path: P) -> Option<&[u8]> {
+        match &self.get(path)?.node {
+            MemoryNode::File(data) => Some(data),
+            _ => None,
+        }
+    }
+
+    /// the entries directly inside the directory `path`, ordered by their paths. An empty
+    /// path stands for the top level of the archive.
+    pub fn children<'a, P: AsRef<Path>>(
+        &'a self,
+        path: P,
+    ) -> impl Iterator<Item = (&'a Path, &'a MemoryEntry)> + 'a {
+        let parent = normalize(path.as_ref());
+        self.iter()
+            .filter(move |(path, _)| path.parent().is_some_and(|p| Some(p) == parent.as_deref()))
+    }
+
+    /// all entries, ordered by their paths
+    pub fn iter(&self) -> impl Iterator<Item = (&Path, &MemoryEntry)> {
+        self.entries
+            .iter()
+            .map(|(path, entry)| (path.as_path(), entry))
+    }
+
+    /// the number of entries
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// does the tree have no entries at all
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// resolves `.` and `..` in `path`, returning `None` if it leads out of the top level
+fn normalize(path: &Path) -> Option<PathBuf> {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(component) => normalized.push(component),
+            Component::CurDir => {}
+            Component::ParentDir if normalized.pop() => {}
+            _ => return None,
+        }
+    }
+    Some(normalized)
+}
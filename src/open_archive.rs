@@ -1,9 +1,15 @@
 use super::error::*;
 use super::*;
+use crate::format::{self, AuthenticityInfo, Locator, MainHeader, RarFormat};
+use crate::limits::{Budget, EntryBudget};
+use crate::sink::Sink;
+use std::cell::OnceCell;
 use std::fmt;
+use std::io;
 use std::os::raw::{c_int, c_uint};
 use std::path::{Path, PathBuf};
 use std::ptr::NonNull;
+use std::time::SystemTime;
 
 bitflags::bitflags! {
     #[derive(Debug, Default)]
@@ -32,17 +38,107 @@ pub enum VolumeInfo {
 }
 
 #[derive(Debug)]
-struct Handle(NonNull<native::Handle>);
+struct Handle {
+    raw: NonNull<native::Handle>,
+    /// the volume that was initially opened
+    path: PathBuf,
+    /// the volume that is currently being read, as reported by the DLL
+    volume: PathBuf,
+    budget: Budget,
+    /// the main header of the initially opened volume, which unrar does not expose. It is
+    /// only read once asked for and is `None` if it could not be read.
+    main: OnceCell<Option<MainHeader>>,
+    /// the password handed to unrar when it asks for one
+    password: Option<SecretPassword>,
+    /// the code page names of RAR 1.5 to 4.x entries are decoded from
+    #[cfg(feature = "encoding")]
+    encoding: Option<&'static encoding_rs::Encoding>,
+}
+
+// SAFETY: the unrar DLL keeps the state of an open archive behind its handle, so a handle
+// may be used from any thread, one thread at a time. The one global it uses, the state of
+// errors in `ErrHandler`, is made thread-local by unrar_sys, which keeps handles on different
+// threads from racing on it. `RAROpenArchiveEx`, the only DLL call reading it, resets it
+// first and reads it on the same thread, so it never carries over from one call to another.
+// The callback registered for a DLL call only ever points at userdata that lives on the
+// stack of that call and is unregistered before the call returns (see `with_callback`),
+// so no pointer into another thread's stack is left behind when the handle is moved.
+// `Handle` is deliberately not `Sync`: the DLL does not synchronize access to a handle.
+unsafe impl Send for Handle {}
+
+impl Handle {
+    /// Registers `callback` with `userdata` for the duration of the DLL call made by `f`.
+    /// The password is lent to `userdata` in the meantime.
+    ///
+    /// Returns the code returned by `f` along with the OS error that was current right after.
+    fn with_callback<T>(
+        &mut self,
+        callback: native::Callback,
+        userdata: &mut Userdata<T>,
+        f: impl FnOnce(*const native::Handle) -> c_int,
+    ) -> (Code, io::Error) {
+        let handle = self.raw.as_ptr() as *const _;
+        userdata.password = self.password.take();
+        unsafe {
+            native::RARSetCallback(handle, Some(callback), userdata as *mut _ as native::LPARAM);
+        }
+        error::clear_os_error();
+        let code = pathed::in_utf8_locale(|| f(handle));
+        let os_error = io::Error::last_os_error();
+        unsafe { native::RARSetCallback(handle, None, 0) };
+        self.password = userdata.password.take();
+        if let Some(volume) = userdata.volume.take() {
+            self.volume = pathed::from_wide(&volume);
+        }
+        (Code::from(code), os_error)
+    }
+
+    /// the main header of the initially opened volume, read on first use
+    fn main(&self) -> Option<&MainHeader> {
+        self.main
+            .get_or_init(|| MainHeader::read(&self.path).ok())
+            .as_ref()
+    }
+
+    /// the format of the initially opened volume, if its main header could be read
+    fn format(&self) -> Option<RarFormat> {
+        self.main().map(|main| main.format.version)
+    }
+
+    /// decodes the name of `header` if this handle has an encoding for the names of its
+    /// format, which only RAR 1.5 to 4.x archives store in legacy code pages
+    #[cfg(feature = "encoding")]
+    fn decode(&self, mut header: FileHeader) -> FileHeader {
+        let legacy = matches!(self.format(), Some(RarFormat::Rar14 | RarFormat::Rar15to4));
+        if let Some(encoding) = self.encoding.filter(|_| legacy) {
+            header.decode_filename(encoding);
+        }
+        header
+    }
+
+    /// creates an error with the context of this handle
+    fn error(&self, code: Code, when: When, os_error: io::Error) -> UnrarError {
+        UnrarError::from(code, when)
+            .with_volume(&self.volume)
+            .with_os_error(os_error)
+    }
+}
 
 impl Drop for Handle {
     fn drop(&mut self) {
-        unsafe { native::RARCloseArchive(self.0.as_ptr() as *const _) };
+        unsafe { native::RARCloseArchive(self.raw.as_ptr() as *const _) };
     }
 }
 
 /// An open RAR archive that can be read or processed.
 ///
 /// See the [OpenArchive chapter](index.html#openarchive) for more information.
+///
+/// # Thread safety
+///
+/// `OpenArchive` is [`Send`]: an archive can be moved to another thread at any point, e.g.
+/// into [`std::thread::spawn`] in the middle of iterating over it. It is not [`Sync`], since
+/// the underlying handle must not be used by multiple threads at the same time.
 #[derive(Debug)]
 pub struct OpenArchive<M: OpenMode, C: Cursor> {
     handle: Handle,
@@ -51,7 +147,17 @@ pub struct OpenArchive<M: OpenMode, C: Cursor> {
     extra: C,
     marker: std::marker::PhantomData<M>,
 }
-type Userdata<T> = (T, Option<widestring::WideCString>);
+
+#[derive(Default)]
+struct Userdata<T> {
+    output: T,
+    /// the volume last reported through `UCM_CHANGEVOLUMEW`
+    volume: Option<widestring::WideCString>,
+    /// what may still be decompressed from the entry being processed
+    budget: Option<EntryBudget>,
+    /// the password of the archive, see `Handle::with_callback`
+    password: Option<SecretPassword>,
+}
 
 mod private {
     use super::native;
@@ -155,6 +261,12 @@ impl<Mode: OpenMode, C: Cursor> OpenArchive<Mode, C> {
         self.flags.contains(ArchiveFlags::RECOVERY)
     }
 
+    /// does the archive carry authenticity verification data, see
+    /// [`signature`](OpenArchive::signature)
+    pub fn is_signed(&self) -> bool {
+        self.flags.contains(ArchiveFlags::SIGNED)
+    }
+
     /// does the archive have comments
     pub fn has_comment(&self) -> bool {
         self.flags.contains(ArchiveFlags::COMMENT)
@@ -184,6 +296,129 @@ impl<Mode: OpenMode, C: Cursor> OpenArchive<Mode, C> {
         }
     }
 
+    /// The version of the RAR format of the archive.
+    ///
+    /// unrar does not tell, so the main header of the *initially* opened volume is read again
+    /// the first time this or any of the other accessors for it is called. Returns `None` if
+    /// that fails, e.g. because the file has been removed since.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use unrar::{Archive, RarFormat};
+    ///
+    /// let archive = Archive::new("data/version.rar").open_for_listing().unwrap();
+    /// assert_eq!(archive.format(), Some(RarFormat::Rar15to4));
+    /// ```
+    pub fn format(&self) -> Option<RarFormat> {
+        self.handle.format()
+    }
+
+    /// decodes the names of RAR 1.5 to 4.x entries read from now on from `encoding`, see
+    /// [`Archive::with_filename_encoding`]
+    #[cfg(feature = "encoding")]
+    pub(crate) fn with_filename_encoding(
+        mut self,
+        encoding: Option<&'static encoding_rs::Encoding>,
+    ) -> Self {
+        self.handle.encoding = encoding;
+        self
+    }
+
+    /// Where the quick open and recovery records of the *initially* opened volume are.
+    ///
+    /// Only RAR 5.0 archives have a locator, and not necessarily.
+    pub fn locator(&self) -> Option<Locator> {
+        self.handle.main()?.locator
+    }
+
+    /// does the *initially* opened volume have a quick open record, which caches copies of
+    /// the headers in that volume at its end.
+    ///
+    /// unrar reads the record by itself where it helps, there is nothing to opt into. Since
+    /// the record only covers the headers of its own volume, listing an archive of several
+    /// volumes still opens every one of them.
+    pub fn has_quick_open(&self) -> bool {
+        self.locator()
+            .is_some_and(|locator| locator.quick_open.is_some())
+    }
+
+    /// The authenticity verification data of a signed archive.
+    ///
+    /// Only RAR 2.x and 3.x could sign archives. Returns `None` if the archive is not signed
+    /// or its AV data is not where the main header says it is.
+    pub fn signature(&self) -> Option<AuthenticityInfo> {
+        self.handle.main()?.signature.clone()
+    }
+
+    /// The name the archive was created with, if recorded, which RAR 5.0 does on request
+    /// (`rar -ams`).
+    pub fn original_name(&self) -> Option<&str> {
+        self.handle.main()?.name.as_deref()
+    }
+
+    /// When the archive was created, if recorded, which RAR 5.0 does on request
+    /// (`rar -ams`).
+    pub fn created(&self) -> Option<std::time::SystemTime> {
+        self.handle.main()?.created
+    }
+
+    /// Opens the data of `entry` for reading and seeking straight from the volumes, without
+    /// unrar, returning `None` if the archive has no such entry.
+    ///
+    /// This only works for entries whose data is stored as is, i.e. neither compressed
+    /// (`entry.method == 0x30`) nor encrypted, which may be split across volumes. The entry
+    /// is looked up among the entries listed from the volume that was initially opened by
+    /// its name, size, CRC and modification time, so entries of the same name are told
+    /// apart unless their data is the same as well. See [`StoredReader`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::io::{Read, Seek, SeekFrom};
+    /// use unrar::Archive;
+    ///
+    /// let mut archive = Archive::new("data/volumes.part1.rar").open_for_listing().unwrap();
+    /// let entry = archive.nth(1).unwrap().unwrap();
+    /// assert_eq!(entry.filename.to_str(), Some("split.txt"));
+    /// let mut reader = archive.open_seekable(&entry).unwrap().unwrap();
+    /// reader.seek(SeekFrom::Start(6)).unwrap();
+    /// let mut data = String::new();
+    /// reader.read_to_string(&mut data).unwrap();
+    /// assert_eq!(data, "across two volumes\n");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error with [`Code::NotStored`] if the data of `entry` is compressed or
+    /// encrypted, one with [`Code::EOpen`] if a volume holding a part of it is missing and
+    /// one with [`Code::BadData`] if the headers read from the volumes do not match `entry`.
+    pub fn open_seekable(&self, entry: &FileHeader) -> UnrarResult<Option<StoredReader>> {
+        if !stored::is_stored(entry) {
+            return Err(stored::not_stored(entry));
+        }
+        // list the archive like this handle does, with its password and encoding
+        let listed = OpenArchive::<List, _>::new(
+            &self.handle.path,
+            self.handle.password.as_ref(),
+            Budget::default(),
+            None,
+        )?;
+        #[cfg(feature = "encoding")]
+        let listed = listed.with_filename_encoding(self.handle.encoding);
+        let mut ordinal = None;
+        for (i, header) in listed.enumerate() {
+            if header?.is_same_entry(entry) {
+                ordinal = Some(i);
+                break;
+            }
+        }
+        match ordinal {
+            Some(ordinal) => stored::open(&self.handle.path, ordinal, entry),
+            None => Ok(None),
+        }
+    }
+
     /// unsets the `damaged` flag so that `Iterator` will not refuse to yield elements.
     ///
     /// Normally, when an error is returned during iteration, the archive remembers this
@@ -228,36 +463,65 @@ impl<Mode: OpenMode, C: Cursor> OpenArchive<Mode, C> {
 impl<Mode: OpenMode> OpenArchive<Mode, CursorBeforeHeader> {
     pub(crate) fn new(
         filename: &Path,
-        password: Option<&[u8]>,
+        password: Option<&SecretPassword>,
+        budget: Budget,
+        recover: Option<&mut Option<Self>>,
+    ) -> UnrarResult<Self> {
+        Self::with_flags(filename, password, budget, 0, recover)
+    }
+
+    /// opens the archive like [`new`](Self::new), passing the `ROADOF_*` flags `op_flags`
+    pub(crate) fn with_flags(
+        filename: &Path,
+        password: Option<&SecretPassword>,
+        budget: Budget,
+        op_flags: c_uint,
         recover: Option<&mut Option<Self>>,
     ) -> UnrarResult<Self> {
-        let filename = pathed::construct(filename);
+        let path = filename;
+        let filename = pathed::construct(path).map_err(|e| UnrarError::nul(e, When::Open))?;
+        if let Some(password) = password {
+            password
+                .check_nul()
+                .map_err(|e| UnrarError::nul(e, When::Open))?;
+        }
 
         let mut data =
-            native::OpenArchiveDataEx::new(filename.as_ptr() as *const _, Mode::VALUE as u32);
-        let handle =
-            NonNull::new(unsafe { native::RAROpenArchiveEx(&mut data as *mut _) } as *mut _);
-
-        let arc = handle.and_then(|handle| {
-            if let Some(pw) = password {
-                let cpw = std::ffi::CString::new(pw).unwrap();
-                unsafe { native::RARSetPassword(handle.as_ptr(), cpw.as_ptr() as *const _) }
-            }
-            Some(OpenArchive {
-                handle: Handle(handle),
-                damaged: false,
-                flags: ArchiveFlags::from_bits(data.flags).unwrap(),
-                extra: CursorBeforeHeader,
-                marker: std::marker::PhantomData,
-            })
+            native::OpenArchiveDataEx::new_wide(filename.as_ptr() as *const _, Mode::VALUE as u32);
+        data.op_flags = op_flags;
+        error::clear_os_error();
+        let handle = pathed::in_utf8_locale(|| unsafe {
+            NonNull::new(native::RAROpenArchiveEx(&mut data as *mut _) as *mut _)
+        });
+        let os_error = io::Error::last_os_error();
+        let result = Code::from(data.open_result as i32);
+
+        let arc = handle.map(|handle| OpenArchive {
+            handle: Handle {
+                raw: handle,
+                path: path.to_path_buf(),
+                volume: path.to_path_buf(),
+                budget,
+                // unrar does not tell which format it has opened, the main header is read
+                // once asked for
+                main: OnceCell::new(),
+                password: password.cloned(),
+                #[cfg(feature = "encoding")]
+                encoding: None,
+            },
+            damaged: false,
+            flags: ArchiveFlags::from_bits_retain(data.flags),
+            extra: CursorBeforeHeader,
+            marker: std::marker::PhantomData,
         });
-        let result = Code::from(data.open_result as i32).unwrap();
 
         match (arc, result) {
             (Some(arc), Code::Success) => Ok(arc),
             (arc, _) => {
                 recover.and_then(|recover| arc.and_then(|arc| recover.replace(arc)));
-                Err(UnrarError::from(result, When::Open))
+                Err(UnrarError::from(result, When::Open)
+                    .with_volume(path)
+                    .with_os_error(os_error))
             }
         }
     }
@@ -277,8 +541,8 @@ impl<Mode: OpenMode> OpenArchive<Mode, CursorBeforeHeader> {
     /// let archive = archive.unwrap().unwrap();
     /// assert_eq!(archive.entry().filename.as_os_str(), "VERSION");
     /// ```
-    pub fn read_header(self) -> UnrarResult<Option<OpenArchive<Mode, CursorBeforeFile>>> {
-        Ok(read_header(&self.handle)?.map(|entry| OpenArchive {
+    pub fn read_header(mut self) -> UnrarResult<Option<OpenArchive<Mode, CursorBeforeFile>>> {
+        Ok(read_header(&mut self.handle)?.map(|entry| OpenArchive {
             extra: CursorBeforeFile { header: entry },
             damaged: self.damaged,
             handle: self.handle,
@@ -295,13 +559,13 @@ impl Iterator for OpenArchive<List, CursorBeforeHeader> {
         if self.damaged {
             return None;
         }
-        match read_header(&self.handle) {
+        match read_header(&mut self.handle) {
             Ok(Some(header)) => {
-                match Internal::<Skip>::process_file_raw(&self.handle, None, None) {
+                match Internal::<Skip>::process_file_raw(&mut self.handle, &header, None, None) {
                     Ok(_) => Some(Ok(header)),
                     Err(s) => {
                         self.damaged = true;
-                        Some(Err(s))
+                        Some(Err(s.with_filename(&header.filename)))
                     }
                 }
             }
@@ -321,13 +585,13 @@ impl Iterator for OpenArchive<ListSplit, CursorBeforeHeader> {
         if self.damaged {
             return None;
         }
-        match read_header(&self.handle) {
+        match read_header(&mut self.handle) {
             Ok(Some(header)) => {
-                match Internal::<Skip>::process_file_raw(&self.handle, None, None) {
+                match Internal::<Skip>::process_file_raw(&mut self.handle, &header, None, None) {
                     Ok(_) => Some(Ok(header)),
                     Err(s) => {
                         self.damaged = true;
-                        Some(Err(s))
+                        Some(Err(s.with_filename(&header.filename)))
                     }
                 }
             }
@@ -364,8 +628,34 @@ impl<M: OpenMode> OpenArchive<M, CursorBeforeFile> {
         path: Option<&pathed::RarStr>,
         file: Option<&pathed::RarStr>,
     ) -> UnrarResult<(PM::Output, OpenArchive<M, CursorBeforeHeader>)> {
-        let result = Ok((
-            Internal::<PM>::process_file_raw(&self.handle, path, file)?,
+        self.process_file_with::<PM>(path, file, Default::default())
+    }
+
+    /// processes the file like [`process_file_x`](Self::process_file_x), handing `output` to
+    /// the callback
+    fn process_file_with<PM: ProcessMode>(
+        self,
+        path: Option<&pathed::RarStr>,
+        file: Option<&pathed::RarStr>,
+        output: PM::Output,
+    ) -> UnrarResult<(PM::Output, OpenArchive<M, CursorBeforeHeader>)> {
+        let (result, archive) = self.process_file_then::<PM>(path, file, output);
+        Ok((result?, archive))
+    }
+
+    /// processes the file like [`process_file_with`](Self::process_file_with), but hands
+    /// back the archive even if processing fails
+    fn process_file_then<PM: ProcessMode>(
+        mut self,
+        path: Option<&pathed::RarStr>,
+        file: Option<&pathed::RarStr>,
+        output: PM::Output,
+    ) -> (UnrarResult<PM::Output>, OpenArchive<M, CursorBeforeHeader>) {
+        let entry = &self.extra.header;
+        let result = Internal::<PM>::process_file_with(&mut self.handle, entry, path, file, output)
+            .map_err(|e| e.with_filename(&entry.filename));
+        (
+            result,
             OpenArchive {
                 extra: CursorBeforeHeader,
                 damaged: self.damaged,
@@ -373,8 +663,7 @@ impl<M: OpenMode> OpenArchive<M, CursorBeforeFile> {
                 flags: self.flags,
                 marker: std::marker::PhantomData,
             },
-        ));
-        result
+        )
     }
 }
 
@@ -382,12 +671,68 @@ impl OpenArchive<Process, CursorBeforeFile> {
     /// Reads the underlying file into a `Vec<u8>`
     /// Returns the data as well as the owned Archive that can be processed further.
     pub fn read(self) -> UnrarResult<(Vec<u8>, OpenArchive<Process, CursorBeforeHeader>)> {
-        Ok(self.process_file_x::<ReadToVec>(None, None)?)
+        self.process_file_x::<ReadToVec>(None, None)
+    }
+
+    /// Reads the underlying file chunk by chunk into `sink`, see [`Sink::write_chunk`].
+    pub(crate) fn read_to_sink(
+        self,
+        sink: &mut dyn Sink,
+    ) -> UnrarResult<OpenArchive<Process, CursorBeforeHeader>> {
+        let filename = self.extra.header.filename.clone();
+        let mut state = SinkState { sink, error: None };
+        let writer = SinkWriter(Some(NonNull::from(&mut state).cast()));
+        let result = self.process_file_with::<ToSink>(None, None, writer);
+        match state.error {
+            // the sink failed and aborted processing
+            Some(error) => {
+                Err(UnrarError::io(Code::EWrite, When::Process, error).with_filename(&filename))
+            }
+            None => Ok(result?.1),
+        }
     }
 
     /// Test the file without extracting it
     pub fn test(self) -> UnrarResult<OpenArchive<Process, CursorBeforeHeader>> {
-        Ok(self.process_file::<Test>(None, None)?)
+        self.process_file::<Test>(None, None)
+    }
+
+    /// Checks the password of the archive against this entry, which should be encrypted,
+    /// see [`Archive::check_password`]. `checked` are the entries of the archive storing a
+    /// check value of the password, see [`services::password_checks`].
+    pub(crate) fn check_password(mut self, checked: &[FileHeader]) -> UnrarResult<bool> {
+        let entry = &self.extra.header;
+        let result = match self.handle.format() {
+            // before RAR 5.0, only the CRC tells once the whole entry is decompressed, which
+            // is also the way to go if the format is not known
+            None | Some(RarFormat::Rar14 | RarFormat::Rar15to4) => {
+                Internal::<Test>::process_file_raw(&mut self.handle, entry, None, None)
+            }
+            // without a check value, unrar decrypts garbage with a wrong password and only
+            // the CRC tells
+            _ if !checked.iter().any(|header| header.is_same_entry(entry)) => {
+                Internal::<Test>::process_file_raw(&mut self.handle, entry, None, None)
+            }
+            // unrar checks the password against the check value before decrypting any data,
+            // so the first chunk proves it right
+            _ => match Internal::<FirstChunk>::process_file_output(
+                &mut self.handle,
+                entry,
+                None,
+                None,
+                false,
+            ) {
+                (_, true) => Ok(()),
+                (result, false) => result,
+            },
+        };
+        match result {
+            Ok(()) => Ok(true),
+            Err(e) => match e.code {
+                Code::BadPassword | Code::MissingPassword | Code::BadData => Ok(false),
+                _ => Err(e.with_filename(&entry.filename)),
+            },
+        }
     }
 
     /// Extracts the file into the current working directory
@@ -399,9 +744,10 @@ impl OpenArchive<Process, CursorBeforeFile> {
     /// Extracts the file into the specified directory.  
     /// Returns the OpenArchive for further processing
     ///
-    /// # Panics
+    /// # Errors
     ///
-    /// This function will panic if `base` contains nul characters.
+    /// Returns an error with [`Code::Nul`](crate::error::Code::Nul) if `base` contains nul
+    /// characters.
     pub fn extract_with_base<P: AsRef<Path>>(
         self,
         base: P,
@@ -412,45 +758,96 @@ impl OpenArchive<Process, CursorBeforeFile> {
     /// Extracts the file into the specified file.
     /// Returns the OpenArchive for further processing
     ///
-    /// # Panics
+    /// # Errors
     ///
-    /// This function will panic if `dest` contains nul characters.
+    /// Returns an error with [`Code::Nul`](crate::error::Code::Nul) if `dest` contains nul
+    /// characters.
     pub fn extract_to<P: AsRef<Path>>(
         self,
         file: P,
     ) -> UnrarResult<OpenArchive<Process, CursorBeforeHeader>> {
-        let dest = pathed::construct(file.as_ref());
+        let dest = pathed::construct(file.as_ref()).map_err(|e| {
+            UnrarError::nul(e, When::Process).with_filename(&self.extra.header.filename)
+        })?;
         self.process_file::<Extract>(None, Some(&dest))
     }
 
-    /// extracting into a directory if the filename has unicode characters
-    /// does not work on Linux, so we must specify the full path for Linux
+    /// Tests the file like [`test`](Self::test) or, given `base`, extracts it like
+    /// [`extract_with_base`](Self::extract_with_base), but hands back the archive even if
+    /// that fails. Whether the archive can still be read depends on the error, see
+    /// [`TestReport`].
+    ///
+    /// If `base` contains nul values, the file is skipped and the error has
+    /// [`Code::Nul`].
+    pub(crate) fn test_then(
+        self,
+        base: Option<&Path>,
+    ) -> (UnrarResult<()>, OpenArchive<Process, CursorBeforeHeader>) {
+        match base {
+            None => self.process_file_then::<Test>(None, None, ()),
+            Some(base) => match pathed::construct(base) {
+                Ok(path) => self.process_file_then::<Extract>(Some(&path), None, ()),
+                Err(e) => {
+                    let error = UnrarError::nul(e, When::Process)
+                        .with_filename(&self.extra.header.filename);
+                    (Err(error), self.process_file_then::<Skip>(None, None, ()).1)
+                }
+            },
+        }
+    }
+
+    /// extracts the file into `base`, or the current directory, keeping its path
     fn dir_extract(
         self,
         base: Option<&Path>,
     ) -> UnrarResult<OpenArchive<Process, CursorBeforeHeader>> {
-        let (path, file) = pathed::preprocess_extract(base, &self.entry().filename);
-        self.process_file::<Extract>(path.as_deref(), file.as_deref())
+        let path = base.map(pathed::construct).transpose().map_err(|e| {
+            UnrarError::nul(e, When::Process).with_filename(&self.extra.header.filename)
+        })?;
+        self.process_file::<Extract>(path.as_deref(), None)
     }
 }
 
-fn read_header(handle: &Handle) -> UnrarResult<Option<FileHeader>> {
+fn read_header(handle: &mut Handle) -> UnrarResult<Option<FileHeader>> {
     let mut userdata: Userdata<<Skip as ProcessMode>::Output> = Default::default();
-    unsafe {
-        native::RARSetCallback(
-            handle.0.as_ptr(),
-            Some(Internal::<Skip>::callback),
-            &mut userdata as *mut _ as native::LPARAM,
-        );
-    }
     let mut header = native::HeaderDataEx::default();
-    let read_result =
-        Code::from(unsafe { native::RARReadHeaderEx(handle.0.as_ptr(), &mut header as *mut _) })
-            .unwrap();
+    let mut target = [0; 1024];
+    header.redir_name = target.as_mut_ptr();
+    header.redir_name_size = target.len() as c_uint;
+    let (read_result, os_error) =
+        handle.with_callback(Internal::<Skip>::callback, &mut userdata, |handle| unsafe {
+            native::RARReadHeaderEx(handle, &mut header as *mut _)
+        });
     match read_result {
-        Code::Success => Ok(Some(header.into())),
+        Code::Success => {
+            let link = LinkKind::from(header.redir_type as u64).map(|kind| {
+                let target = unsafe {
+                    widestring::WideCString::from_ptr_truncate(
+                        target.as_ptr() as *const _,
+                        target.len(),
+                    )
+                };
+                Link {
+                    kind,
+                    target: pathed::from_wide(&target),
+                    directory: header.dir_target != 0,
+                }
+            });
+            let header = FileHeader {
+                link,
+                ..FileHeader::from(header)
+            };
+            #[cfg(feature = "encoding")]
+            let header = handle.decode(header);
+            match handle.budget.check_header(&header) {
+                Ok(()) => Ok(Some(header)),
+                Err(limit) => Err(UnrarError::limit_exceeded(limit, When::Read)
+                    .with_filename(&header.filename)
+                    .with_volume(&handle.volume)),
+            }
+        }
         Code::EndArchive => Ok(None),
-        _ => Err(UnrarError::from(read_result, When::Read)),
+        _ => Err(handle.error(read_result, When::Read, os_error)),
     }
 }
 
@@ -462,40 +859,94 @@ struct ReadToVec;
 struct Extract;
 #[derive(Debug)]
 struct Test;
+#[derive(Debug)]
+struct ToSink;
+/// tests up to the first chunk of data
+#[derive(Debug)]
+struct FirstChunk;
 
 trait ProcessMode: core::fmt::Debug {
     const OPERATION: private::Operation;
     type Output: core::fmt::Debug + std::default::Default;
 
-    fn process_data(data: &mut Self::Output, other: &[u8]);
+    /// hands the next chunk of data to `data`, returning `false` to abort processing
+    fn process_data(data: &mut Self::Output, other: &[u8]) -> bool;
 }
 impl ProcessMode for Skip {
     const OPERATION: private::Operation = private::Operation::Skip;
     type Output = ();
 
-    fn process_data(_: &mut Self::Output, _: &[u8]) {}
+    fn process_data(_: &mut Self::Output, _: &[u8]) -> bool {
+        true
+    }
 }
 impl ProcessMode for ReadToVec {
     const OPERATION: private::Operation = private::Operation::Test;
     type Output = Vec<u8>;
 
-    fn process_data(my: &mut Self::Output, other: &[u8]) {
+    fn process_data(my: &mut Self::Output, other: &[u8]) -> bool {
         my.extend_from_slice(other);
+        true
     }
 }
 impl ProcessMode for Extract {
     const OPERATION: private::Operation = private::Operation::Extract;
     type Output = ();
 
-    fn process_data(_: &mut Self::Output, _: &[u8]) {}
+    fn process_data(_: &mut Self::Output, _: &[u8]) -> bool {
+        true
+    }
 }
 impl ProcessMode for Test {
     const OPERATION: private::Operation = private::Operation::Test;
     type Output = ();
 
-    fn process_data(_: &mut Self::Output, _: &[u8]) {}
+    fn process_data(_: &mut Self::Output, _: &[u8]) -> bool {
+        true
+    }
+}
+
+impl ProcessMode for FirstChunk {
+    const OPERATION: private::Operation = private::Operation::Test;
+    /// has data arrived
+    type Output = bool;
+
+    fn process_data(arrived: &mut Self::Output, _: &[u8]) -> bool {
+        *arrived = true;
+        false
+    }
+}
+
+impl ProcessMode for ToSink {
+    const OPERATION: private::Operation = private::Operation::Test;
+    type Output = SinkWriter;
+
+    fn process_data(writer: &mut Self::Output, other: &[u8]) -> bool {
+        let Some(state) = writer.0 else {
+            return true;
+        };
+        // the state outlives processing the file, see `read_to_sink`
+        let state = unsafe { &mut *state.cast::<SinkState>().as_ptr() };
+        match state.sink.write_chunk(other) {
+            Ok(()) => true,
+            Err(error) => {
+                state.error = Some(error);
+                false
+            }
+        }
+    }
+}
+
+/// the sink an entry is read into, along with the error it failed with
+struct SinkState<'a> {
+    sink: &'a mut dyn Sink,
+    error: Option<io::Error>,
 }
 
+/// points to the [`SinkState`] of the entry being read
+#[derive(Debug, Default)]
+struct SinkWriter(Option<NonNull<()>>);
+
 struct Internal<M: ProcessMode> {
     marker: std::marker::PhantomData<M>,
 }
@@ -513,11 +964,17 @@ impl<M: ProcessMode> Internal<M> {
         let user_data = unsafe { &mut *(user_data as *mut Userdata<M::Output>) };
         match msg {
             native::UCM_CHANGEVOLUMEW => {
-                // 2048 seems to be the buffer size in unrar,
-                // also it's the maximum path length since 5.00.
-                let next =
-                    unsafe { widestring::WideCString::from_ptr_truncate(p1 as *const _, 2048) };
-                user_data.1 = Some(next);
+                // the name is nul-terminated, but only for RAR_VOL_ASK is it passed in a buffer
+                // of the maximum path length. For RAR_VOL_NOTIFY, it is exactly as long as the
+                // name, so reading any further than the nul would be out of bounds.
+                let next = unsafe { widestring::WideCStr::from_ptr_str(p1 as *const _) };
+                let next = next.to_ucstring();
+                if p2 == native::RAR_VOL_NOTIFY {
+                    if let Some(budget) = &mut user_data.budget {
+                        budget.next_volume(&pathed::from_wide(&next));
+                    }
+                }
+                user_data.volume = Some(next);
                 match p2 {
                     // Next volume not found. -1 means stop
                     native::RAR_VOL_ASK => -1,
@@ -525,45 +982,87 @@ impl<M: ProcessMode> Internal<M> {
                     _ => 0,
                 }
             }
+            native::UCM_NEEDPASSWORDW => match &user_data.password {
+                // p1 is a buffer of p2 wide characters, which unrar wipes once it is done
+                Some(password) => {
+                    unsafe { password.copy_to(p1 as *mut _, p2 as usize) };
+                    0
+                }
+                // -1 leaves unrar without a password
+                None => -1,
+            },
             native::UCM_PROCESSDATA => {
+                if let Some(budget) = &mut user_data.budget {
+                    if !budget.write(p2 as _) {
+                        // -1 aborts the operation before the data is used
+                        return -1;
+                    }
+                }
                 let raw_slice = std::ptr::slice_from_raw_parts(p1 as *const u8, p2 as _);
-                M::process_data(&mut user_data.0, unsafe { &*raw_slice as &_ });
-                0
+                match M::process_data(&mut user_data.output, unsafe { &*raw_slice as &_ }) {
+                    true => 0,
+                    false => -1,
+                }
             }
             _ => 0,
         }
     }
 
     fn process_file_raw(
-        handle: &Handle,
+        handle: &mut Handle,
+        entry: &FileHeader,
         path: Option<&pathed::RarStr>,
         file: Option<&pathed::RarStr>,
     ) -> UnrarResult<M::Output> {
-        let mut user_data: Userdata<M::Output> = Default::default();
-        unsafe {
-            native::RARSetCallback(
-                handle.0.as_ptr(),
-                Some(Self::callback),
-                &mut user_data as *mut _ as native::LPARAM,
-            );
-        }
-        let process_result = Code::from(pathed::process_file(
-            handle.0.as_ptr(),
-            M::OPERATION as i32,
-            path,
-            file,
-        ))
-        .unwrap();
-        match process_result {
-            Code::Success => Ok(user_data.0),
-            _ => Err(UnrarError::from(process_result, When::Process)),
-        }
+        Self::process_file_with(handle, entry, path, file, Default::default())
+    }
+
+    fn process_file_with(
+        handle: &mut Handle,
+        entry: &FileHeader,
+        path: Option<&pathed::RarStr>,
+        file: Option<&pathed::RarStr>,
+        output: M::Output,
+    ) -> UnrarResult<M::Output> {
+        let (result, output) = Self::process_file_output(handle, entry, path, file, output);
+        result.map(|()| output)
+    }
+
+    /// processes the file like `process_file_with`, but hands back the output even if
+    /// processing failed, e.g. because `M` aborted it
+    fn process_file_output(
+        handle: &mut Handle,
+        entry: &FileHeader,
+        path: Option<&pathed::RarStr>,
+        file: Option<&pathed::RarStr>,
+        output: M::Output,
+    ) -> (UnrarResult<()>, M::Output) {
+        let mut user_data: Userdata<M::Output> = Userdata {
+            output,
+            budget: Some(handle.budget.entry(entry)),
+            ..Default::default()
+        };
+        let (process_result, os_error) =
+            handle.with_callback(Self::callback, &mut user_data, |handle| {
+                pathed::process_file(handle, M::OPERATION as i32, path, file)
+            });
+        let result = match (
+            user_data.budget.and_then(|budget| budget.exceeded),
+            process_result,
+        ) {
+            (Some(limit), _) => {
+                Err(UnrarError::limit_exceeded(limit, When::Process).with_volume(&handle.volume))
+            }
+            (None, Code::Success) => Ok(()),
+            (None, _) => Err(handle.error(process_result, When::Process, os_error)),
+        };
+        (result, user_data.output)
     }
 }
 
 bitflags::bitflags! {
-    #[derive(Debug)]
-    struct EntryFlags: u32 {
+    #[derive(Debug, Clone, Copy)]
+    pub(crate) struct EntryFlags: u32 {
         const SPLIT_BEFORE = 0x1;
         const SPLIT_AFTER = 0x2;
         const ENCRYPTED = 0x4;
@@ -578,18 +1077,142 @@ bitflags::bitflags! {
 /// Created using the read_header methods in an OpenArchive, contains
 /// information for the file that follows which is to be processed next.
 #[allow(missing_docs)]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct FileHeader {
     pub filename: PathBuf,
-    flags: EntryFlags,
+    pub(crate) flags: EntryFlags,
     pub unpacked_size: u64,
+    /// the size of the entry's data in this volume, compressed and possibly encrypted
+    pub packed_size: u64,
     pub file_crc: u32,
     pub file_time: u32,
     pub method: u32,
     pub file_attr: u32,
+    pub(crate) kind: EntryKind,
+    pub(crate) link: Option<Link>,
+    pub(crate) modified: Option<SystemTime>,
+    /// does `file_attr` hold Unix permissions, which it does for entries archived on Unix
+    pub(crate) unix: bool,
+    /// the size of the dictionary in bytes, as unrar computes it
+    pub(crate) dict_size: u64,
+    /// the name as the bytes unrar converts the wide one back to
+    pub(crate) raw_filename: Vec<u8>,
+}
+
+/// A link an entry stands for instead of a file, see [`FileHeader::link`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Link {
+    #[allow(missing_docs)]
+    pub kind: LinkKind,
+    /// the path the link points to, as stored in the archive.
+    ///
+    /// Empty for symbolic links of RAR 4.x archives, which store it as their data instead,
+    /// which unrar only hands out when creating the link.
+    pub target: PathBuf,
+    /// does the link point to a directory, which Windows needs to know for symbolic links
+    pub directory: bool,
+}
+
+/// The kind of a [`Link`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkKind {
+    /// a symbolic link created on Unix
+    UnixSymlink,
+    /// a symbolic link created on Windows
+    WindowsSymlink,
+    /// an NTFS junction point
+    Junction,
+    /// a hard link to an earlier entry of the archive
+    Hardlink,
+    /// a copy of an earlier entry of the archive, stored as a reference to it
+    FileCopy,
+}
+
+impl LinkKind {
+    /// maps unrar's `FILE_SYSTEM_REDIRECT`
+    pub(crate) fn from(redirect: u64) -> Option<Self> {
+        match redirect {
+            1 => Some(LinkKind::UnixSymlink),
+            2 => Some(LinkKind::WindowsSymlink),
+            3 => Some(LinkKind::Junction),
+            4 => Some(LinkKind::Hardlink),
+            5 => Some(LinkKind::FileCopy),
+            _ => None,
+        }
+    }
+}
+
+/// How the data of an entry is compressed, see [`FileHeader::compression`].
+///
+/// The variants are ordered from the fastest to the best compression, which is also the
+/// order of the effort it takes to decompress.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Method {
+    /// not compressed at all
+    Store,
+    #[allow(missing_docs)]
+    Fastest,
+    #[allow(missing_docs)]
+    Fast,
+    #[allow(missing_docs)]
+    Normal,
+    #[allow(missing_docs)]
+    Good,
+    #[allow(missing_docs)]
+    Best,
+    /// A method this crate does not know about, holding [`FileHeader::method`].
+    Other(u32),
+}
+
+impl Method {
+    /// maps the method unrar reports, which is 0x30 to 0x35 for all RAR formats
+    pub(crate) fn from(method: u32) -> Self {
+        match method {
+            0x30 => Method::Store,
+            0x31 => Method::Fastest,
+            0x32 => Method::Fast,
+            0x33 => Method::Normal,
+            0x34 => Method::Good,
+            0x35 => Method::Best,
+            method => Method::Other(method),
+        }
+    }
+}
+
+/// What an entry listed by [`Archive::list`](crate::Archive::list) stands for.
+///
+/// Besides files and directories, archives carry service headers, which unrar only reports
+/// when listing with [`ListOptions::include_services`](crate::ListOptions::include_services).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EntryKind {
+    /// a file or directory
+    File,
+    /// an NTFS alternate data stream called `name` of the file `parent`, which unrar only
+    /// restores on Windows. The entry's filename is `parent:name`.
+    Stream {
+        /// the file the stream belongs to
+        parent: PathBuf,
+        /// the name of the stream, without the leading colon
+        name: String,
+    },
+    /// any other service header, e.g. `CMT` for the archive comment, `QO` for the quick
+    /// open record or `ACL` for NTFS security descriptors. The entry's filename is `name`.
+    Service {
+        /// the name of the service header
+        name: String,
+    },
 }
 
 impl FileHeader {
+    /// do both headers describe the same entry, as far as [`List`] and [`Process`] mode
+    /// agree: entries alike in name, size, CRC and modification time are taken as one
+    pub(crate) fn is_same_entry(&self, other: &FileHeader) -> bool {
+        self.filename == other.filename
+            && self.unpacked_size == other.unpacked_size
+            && self.file_crc == other.file_crc
+            && self.file_time == other.file_time
+    }
+
     /// is this entry split across multiple volumes.
     ///
     /// Will also work in open mode [`List`]
@@ -629,6 +1252,93 @@ impl FileHeader {
     pub fn is_file(&self) -> bool {
         !self.is_directory()
     }
+
+    /// how the data of this entry is compressed
+    pub fn compression(&self) -> Method {
+        Method::from(self.method)
+    }
+
+    /// Does this entry continue the solid stream of the entries before it, i.e. can it only
+    /// be decompressed after them.
+    ///
+    /// Such entries have to be decoded even when skipping them, since the entries after
+    /// them may depend on them just as well. The first entry of a solid archive starts the
+    /// stream and is not a continuation. RAR versions before 2.0 do not mark entries, see
+    /// [`OpenArchive::is_solid`] instead.
+    pub fn is_solid_continuation(&self) -> bool {
+        self.flags.contains(EntryFlags::SOLID)
+    }
+
+    /// The size of the dictionary, i.e. the window of previous data, in bytes which it
+    /// takes to decompress this entry.
+    ///
+    /// This is what decompressing takes in memory beyond the buffers. It is 0 for
+    /// directories and for entries of formats unrar cannot decompress.
+    pub fn dictionary_size(&self) -> u64 {
+        self.dict_size
+    }
+
+    /// The name of the entry as the bytes unrar hands out besides [`filename`](Self::filename).
+    ///
+    /// These are not read from the archive: unrar converts the wide name it decoded back to
+    /// bytes in the code page of the system. On Unix other than macOS, names that RAR 1.5 to
+    /// 4.x archives store in a legacy code page rather than as Unicode come back as the bytes
+    /// stored, which
+    /// [`Archive::with_filename_encoding`](crate::Archive::with_filename_encoding) decodes,
+    /// and other names come out in UTF-8.
+    ///
+    /// On Windows and macOS, the conversion goes through the code pages of the system, which
+    /// may not represent every character and need not match the one the archive was created
+    /// with, so the bytes may differ from the stored ones.
+    pub fn raw_filename(&self) -> &[u8] {
+        &self.raw_filename
+    }
+
+    /// decodes `filename` from `raw_filename` unless it is valid UTF-8, which is how unrar
+    /// hands out names stored as Unicode on Unix
+    #[cfg(feature = "encoding")]
+    pub(crate) fn decode_filename(&mut self, encoding: &'static encoding_rs::Encoding) {
+        if std::str::from_utf8(&self.raw_filename).is_err() {
+            let (name, _, _) = encoding.decode(&self.raw_filename);
+            self.filename = PathBuf::from(name.into_owned());
+        }
+    }
+
+    /// what this entry stands for, which is always [`EntryKind::File`] unless the entry was
+    /// listed with [`ListOptions::include_services`](crate::ListOptions::include_services)
+    pub fn kind(&self) -> &EntryKind {
+        &self.kind
+    }
+
+    /// the link this entry stands for, if it is one. Reading such an entry yields no data.
+    pub fn link(&self) -> Option<&Link> {
+        self.link.as_ref()
+    }
+
+    /// the modification time of this entry in UTC, unlike
+    /// [`file_time`](FileHeader::file_time) which is in local time and MS-DOS format
+    pub fn modified(&self) -> Option<SystemTime> {
+        self.modified
+    }
+
+    /// the Unix mode of this entry, i.e. its permissions and file type, if it was archived
+    /// on Unix. Otherwise [`file_attr`](FileHeader::file_attr) holds Windows attributes.
+    pub fn unix_mode(&self) -> Option<u32> {
+        self.unix.then_some(self.file_attr)
+    }
+
+    /// the Unix permissions of this entry, made up from its Windows attributes if it has no
+    /// Unix mode
+    #[cfg(any(feature = "convert", all(feature = "fuse", target_os = "linux")))]
+    pub(crate) fn permissions(&self) -> u32 {
+        const FILE_ATTRIBUTE_READONLY: u32 = 0x1;
+        match self.unix_mode() {
+            Some(mode) => mode & 0o7777,
+            None if self.is_directory() => 0o755,
+            None if self.file_attr & FILE_ATTRIBUTE_READONLY != 0 => 0o444,
+            None => 0o644,
+        }
+    }
 }
 
 impl fmt::Display for FileHeader {
@@ -649,19 +1359,38 @@ impl From<native::HeaderDataEx> for FileHeader {
         let filename = unsafe {
             widestring::WideCString::from_ptr_truncate(header.filename_w.as_ptr() as *const _, 1024)
         };
+        let raw_filename = header
+            .filename
+            .iter()
+            .take_while(|&&c| c != 0)
+            .map(|&c| c as u8)
+            .collect();
 
         FileHeader {
-            filename: PathBuf::from(filename.to_os_string()),
-            flags: EntryFlags::from_bits(header.flags).unwrap(),
+            filename: pathed::from_wide(&filename),
+            flags: EntryFlags::from_bits_retain(header.flags),
             unpacked_size: unpack_unp_size(header.unp_size, header.unp_size_high),
+            packed_size: unpack_unp_size(header.pack_size, header.pack_size_high),
             file_crc: header.file_crc,
             file_time: header.file_time,
             method: header.method,
             file_attr: header.file_attr,
+            kind: EntryKind::File,
+            link: None,
+            modified: match (header.mtime_high as u64) << 32 | header.mtime_low as u64 {
+                0 => None,
+                ticks => format::filetime(ticks),
+            },
+            unix: header.host_os == HOST_UNIX,
+            dict_size: header.dict_size as u64 * 1024,
+            raw_filename,
         }
     }
 }
 
+/// `HOST_UNIX` in unrar, which the DLL reports for RAR 5.0 entries from Unix as well
+const HOST_UNIX: c_uint = 3;
+
 fn unpack_unp_size(unp_size: c_uint, unp_size_high: c_uint) -> u64 {
     ((unp_size_high as u64) << (8 * std::mem::size_of::<c_uint>())) | (unp_size as u64)
 }
@@ -0,0 +1,221 @@
+use crate::archive::Archive;
+use crate::error::{UnrarError, UnrarResult};
+use crate::limits::Budget;
+use crate::open_archive::{CursorBeforeHeader, FileHeader, OpenArchive, Process};
+use crate::password::SecretPassword;
+use std::iter::Enumerate;
+use std::ops::Range;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, PoisonError};
+
+/// Extracts all entries of the archive at `filename` into `dest` using `workers` threads.
+///
+/// Every worker opens its own handle on the archive, the underlying handle cannot be shared.
+/// A first pass on the calling thread reads all headers, creates the directories and weighs
+/// the files by their packed size. Each worker then extracts a contiguous range of entries of
+/// about the same weight, skipping over the entries before it and stopping at its end, which
+/// is cheap for non-solid archives since skipping does not decompress anything. Solid
+/// archives are extracted by the first pass alone.
+///
+/// If any worker fails, the error of the entry that comes first in the archive is returned.
+/// The workers share `budget`, so limits on the total size apply to all of them together.
+pub(crate) fn extract_all(
+    filename: &Path,
+    password: Option<&SecretPassword>,
+    budget: &Budget,
+    dest: &Path,
+    workers: usize,
+) -> UnrarResult<()> {
+    let archive = OpenArchive::<Process, _>::new(filename, password, budget.clone(), None)?;
+    if archive.is_solid() || workers == 1 {
+        return extract_range(archive, dest, 0..usize::MAX).map_err(|(_, e)| e);
+    }
+    let (weights, failed) = prepare(archive, dest);
+    let results = std::thread::scope(|scope| {
+        let handles = ranges(&weights, workers)
+            .map(|range| {
+                let budget = budget.clone();
+                scope.spawn(move || {
+                    OpenArchive::<Process, _>::new(filename, password, budget, None)
+                        .map_err(|e| (range.start, e))
+                        .and_then(|archive| extract_range(archive, dest, range))
+                })
+            })
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .map(|handle| {
+                handle
+                    .join()
+                    .unwrap_or_else(|e| std::panic::resume_unwind(e))
+            })
+            .collect::<Vec<_>>()
+    });
+    match results
+        .into_iter()
+        .filter_map(Result::err)
+        .chain(failed)
+        .min_by_key(|(offset, _)| *offset)
+    {
+        Some((_, error)) => Err(error),
+        None => Ok(()),
+    }
+}
+
+/// Reads all headers of `archive`, extracting directories into `dest` and skipping files.
+///
+/// Returns the weight of each entry, its packed size for files and nothing for directories,
+/// along with the error that ended the walk early and the offset it occurred at. The entries
+/// before that offset can still be extracted.
+fn prepare(
+    mut archive: OpenArchive<Process, CursorBeforeHeader>,
+    dest: &Path,
+) -> (Vec<u64>, Option<(usize, UnrarError)>) {
+    let mut weights = Vec::new();
+    let failed = loop {
+        let header = match archive.read_header() {
+            Ok(Some(header)) => header,
+            Ok(None) => break None,
+            Err(e) => break Some(e),
+        };
+        let entry = header.entry();
+        // continuation headers of split entries are only seen when the beginning of the
+        // entry was skipped. They do not count, just like in `ArchiveIndex`.
+        let continuation = entry.is_split_before();
+        let weight = (!entry.is_directory()).then(|| entry.packed_size.saturating_add(1));
+        let result = match weight {
+            None if !continuation => header.extract_with_base(dest),
+            _ => header.skip(),
+        };
+        archive = match result {
+            Ok(archive) => archive,
+            Err(e) => break Some(e),
+        };
+        if !continuation {
+            weights.push(weight.unwrap_or(0));
+        }
+    };
+    let offset = weights.len();
+    (weights, failed.map(|e| (offset, e)))
+}
+
+/// divides the entries into at most `workers` contiguous ranges of about the same weight
+fn ranges(weights: &[u64], workers: usize) -> impl Iterator<Item = Range<usize>> {
+    let total = weights.iter().map(|&w| u128::from(w)).sum::<u128>();
+    let mut sum = 0;
+    let mut ends = weights
+        .iter()
+        .enumerate()
+        .filter_map(|(offset, &weight)| {
+            let before = sum * workers as u128 / total.max(1);
+            sum += u128::from(weight);
+            (sum * workers as u128 / total.max(1) > before).then_some(offset + 1)
+        })
+        .collect::<Vec<_>>();
+    // trailing entries without weight go to the last range
+    if let Some(last) = ends.last_mut() {
+        *last = weights.len();
+    }
+    let starts = std::iter::once(0).chain(ends.clone());
+    starts.zip(ends).map(|(start, end)| start..end)
+}
+
+/// extracts the entries at offsets within `range`, skipping over those before it. Errors
+/// come with the offset of the entry they occurred at.
+fn extract_range(
+    mut archive: OpenArchive<Process, CursorBeforeHeader>,
+    dest: &Path,
+    range: Range<usize>,
+) -> Result<(), (usize, UnrarError)> {
+    let mut offset = 0;
+    while offset < range.end {
+        let Some(header) = archive.read_header().map_err(|e| (offset, e))? else {
+            break;
+        };
+        let continuation = header.entry().is_split_before();
+        let result = if !continuation && range.contains(&offset) {
+            header.extract_with_base(dest)
+        } else {
+            header.skip()
+        };
+        archive = result.map_err(|e| (offset, e))?;
+        if !continuation {
+            offset += 1;
+        }
+    }
+    Ok(())
+}
+
+/// Checks `candidates` against `archive` using `workers` threads, see
+/// [`Archive::try_passwords_parallel`].
+///
+/// The workers take candidates off the shared iterator until one of them finds a fitting
+/// candidate or fails, after which they only finish the candidates that come before it.
+pub(crate) fn try_passwords<I>(
+    archive: &Archive,
+    checked: &[FileHeader],
+    candidates: I,
+    workers: usize,
+) -> UnrarResult<Option<usize>>
+where
+    I: Iterator + Send,
+    I::Item: Into<SecretPassword>,
+{
+    let candidates = Mutex::new(candidates.enumerate());
+    // the offset of the first candidate known to fit or fail
+    let end = AtomicUsize::new(usize::MAX);
+    let results = std::thread::scope(|scope| {
+        let handles = (0..workers)
+            .map(|_| scope.spawn(|| try_share(archive, checked, &candidates, &end)))
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .flat_map(|handle| {
+                handle
+                    .join()
+                    .unwrap_or_else(|e| std::panic::resume_unwind(e))
+            })
+            .collect::<Vec<_>>()
+    });
+    match results.into_iter().min_by_key(|(offset, _)| *offset) {
+        Some((offset, Ok(()))) => Ok(Some(offset)),
+        Some((_, Err(error))) => Err(error),
+        None => Ok(None),
+    }
+}
+
+/// checks candidates until none are left before `end`, returning those that fit or fail
+/// along with their offsets
+fn try_share<I>(
+    archive: &Archive,
+    checked: &[FileHeader],
+    candidates: &Mutex<Enumerate<I>>,
+    end: &AtomicUsize,
+) -> Vec<(usize, UnrarResult<()>)>
+where
+    I: Iterator,
+    I::Item: Into<SecretPassword>,
+{
+    let mut found = Vec::new();
+    loop {
+        let next = candidates
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .next();
+        let Some((offset, candidate)) = next else {
+            break;
+        };
+        if offset > end.load(Ordering::Relaxed) {
+            break;
+        }
+        let result = match archive.check_secret_password(&candidate.into(), checked) {
+            Ok(false) => continue,
+            Ok(true) => Ok(()),
+            Err(error) => Err(error),
+        };
+        end.fetch_min(offset, Ordering::Relaxed);
+        found.push((offset, result));
+    }
+    found
+}
@@ -0,0 +1,158 @@
+//! Passwords that are wiped from memory once dropped, see [`SecretPassword`].
+
+use crate::error::{Code, NulError, UnrarError, UnrarResult};
+use crate::open_archive::{CursorBeforeHeader, FileHeader, OpenArchive, Process};
+use std::char::DecodeUtf16Error;
+use std::fmt;
+use widestring::WideChar;
+use zeroize::{Zeroize, Zeroizing};
+
+/// A password for encrypted archives, held as the wide string unrar takes and wiped from
+/// memory once dropped.
+///
+/// unrar receives the password as a wide string when it asks for it, rather than as bytes
+/// converted through the locale, so non-ASCII passwords work the same on all platforms.
+/// The copies unrar makes are wiped by unrar itself.
+///
+/// # Example
+///
+/// ```
+/// use unrar::{Archive, SecretPassword};
+///
+/// let password = SecretPassword::from("pässwörd✓");
+/// let archive = Archive::with_secret_password("data/unicode-password.rar", password)
+///     .open_for_processing()
+///     .unwrap();
+/// let (data, _) = archive.read_header().unwrap().unwrap().read().unwrap();
+/// assert_eq!(data, b"secret\n");
+/// ```
+#[derive(Clone)]
+pub struct SecretPassword(Zeroizing<Vec<WideChar>>);
+
+impl SecretPassword {
+    /// Creates a password from UTF-16, e.g. as read from a Windows API, failing on unpaired
+    /// surrogates.
+    pub fn from_utf16(password: &[u16]) -> Result<Self, DecodeUtf16Error> {
+        // the capacity suffices, so no copy is left behind by growing the vector
+        let mut wide = Zeroizing::new(Vec::with_capacity(password.len()));
+        for c in char::decode_utf16(password.iter().copied()) {
+            push(&mut wide, c?);
+        }
+        Ok(SecretPassword(wide))
+    }
+
+    /// Creates a password from bytes, which are taken as UTF-8.
+    ///
+    /// Bytes that are not valid UTF-8, e.g. of a password in a legacy code page, are kept
+    /// the way unrar keeps a password handed to it as bytes in a UTF-8 locale: each one is
+    /// mapped into the private use area behind a U+FFFE mark. This gives the same key as
+    /// passing the bytes to unrar directly, and RAR 1.5 to 2.x encryption, which works on
+    /// bytes, gets the original bytes back.
+    pub fn from_bytes(password: &[u8]) -> Self {
+        // the capacity suffices, so no copy is left behind by growing the vector
+        let mut wide = Zeroizing::new(Vec::with_capacity(password.len() + 1));
+        let mut marked = false;
+        for chunk in password.utf8_chunks() {
+            chunk.valid().chars().for_each(|c| push(&mut wide, c));
+            for &byte in chunk.invalid() {
+                if !marked {
+                    push(&mut wide, MAPPED_STRING_MARK);
+                    marked = true;
+                }
+                push(&mut wide, map_byte(byte));
+            }
+        }
+        SecretPassword(wide)
+    }
+
+    /// fails if the password contains nul characters, which would cut it short for unrar
+    pub(crate) fn check_nul(&self) -> Result<(), NulError> {
+        match self.0.iter().position(|&c| c == 0) {
+            Some(position) => Err(NulError::at(position)),
+            None => Ok(()),
+        }
+    }
+
+    /// Copies the password into the nul-terminated wide string buffer `buf` of unrar.
+    ///
+    /// # Safety
+    ///
+    /// `buf` must be valid for writing `len` wide characters.
+    pub(crate) unsafe fn copy_to(&self, buf: *mut WideChar, len: usize) {
+        let Some(max) = len.checked_sub(1) else {
+            return;
+        };
+        let n = self.0.len().min(max);
+        std::ptr::copy_nonoverlapping(self.0.as_ptr(), buf, n);
+        *buf.add(n) = 0;
+    }
+}
+
+/// marks a string with bytes mapped by [`map_byte`], like `MappedStringMark` of unrar
+const MAPPED_STRING_MARK: char = '\u{fffe}';
+
+/// maps a byte that is not valid UTF-8, which is at least 0x80, like `CharToWideMap` of unrar
+fn map_byte(byte: u8) -> char {
+    char::from_u32(0xe000 + u32::from(byte)).expect("within the private use area")
+}
+
+/// appends `c` like the wide strings of the platform hold it
+fn push(wide: &mut Vec<WideChar>, c: char) {
+    #[cfg(windows)]
+    wide.extend_from_slice(c.encode_utf16(&mut [0; 2]));
+    #[cfg(not(windows))]
+    wide.push(c as WideChar);
+}
+
+impl From<&str> for SecretPassword {
+    fn from(password: &str) -> Self {
+        SecretPassword::from_bytes(password.as_bytes())
+    }
+}
+
+/// Takes over the password, wiping `password`.
+impl From<String> for SecretPassword {
+    fn from(mut password: String) -> Self {
+        let secret = SecretPassword::from(password.as_str());
+        password.zeroize();
+        secret
+    }
+}
+
+/// Checks the password `archive` was opened with against its first encrypted entry, or
+/// against its headers if those are encrypted, see [`OpenArchive::check_password`] for
+/// `checked`.
+pub(crate) fn check(
+    archive: UnrarResult<OpenArchive<Process, CursorBeforeHeader>>,
+    checked: &[FileHeader],
+) -> UnrarResult<bool> {
+    let mut archive = match archive {
+        Ok(archive) => archive,
+        Err(e) if is_rejected(&e) => return Ok(false),
+        Err(e) => return Err(e),
+    };
+    loop {
+        let header = match archive.read_header() {
+            Ok(Some(header)) => header,
+            // nothing is encrypted, so any password will do
+            Ok(None) => return Ok(true),
+            Err(e) if is_rejected(&e) => return Ok(false),
+            Err(e) => return Err(e),
+        };
+        if header.entry().is_encrypted() {
+            return header.check_password(checked);
+        }
+        archive = header.skip()?;
+    }
+}
+
+/// did opening the archive or reading its encrypted headers fail for the password
+fn is_rejected(error: &UnrarError) -> bool {
+    matches!(error.code, Code::BadPassword | Code::MissingPassword)
+}
+
+impl fmt::Debug for SecretPassword {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SecretPassword(..)")
+    }
+}
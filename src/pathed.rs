@@ -0,0 +1,133 @@
+//! Paths as passed to and received from the DLL, which are wide strings on all platforms.
+//!
+//! On Unix, `wchar_t` holds UTF-32 and unrar converts it to the bytes of the file system
+//! through the C library. Bytes that are not valid UTF-8 are handed over the way unrar maps
+//! them itself: to the private use area at 0xE000, marked once by 0xFFFE, which unrar turns
+//! back into the original bytes.
+
+use crate::error::NulError;
+use std::path::{Path, PathBuf};
+use widestring::{WideCStr, WideCString};
+
+pub(crate) type RarString = WideCString;
+pub(crate) type RarStr = WideCStr;
+
+#[cfg(unix)]
+const MAP_AREA_START: u32 = 0xE000;
+#[cfg(unix)]
+const MAPPED_STRING_MARK: u32 = 0xFFFE;
+
+#[cfg(not(unix))]
+pub(crate) fn construct(path: &Path) -> Result<RarString, NulError> {
+    Ok(WideCString::from_os_str(path)?)
+}
+
+#[cfg(unix)]
+pub(crate) fn construct(path: &Path) -> Result<RarString, NulError> {
+    use std::os::unix::ffi::OsStrExt;
+    let bytes = path.as_os_str().as_bytes();
+    // report the position within the path rather than within the wide string
+    if let Some(position) = bytes.iter().position(|&b| b == 0) {
+        return Err(NulError::at(position));
+    }
+    let mut wide = Vec::with_capacity(bytes.len() + 1);
+    let mut marked = false;
+    for chunk in bytes.utf8_chunks() {
+        wide.extend(chunk.valid().chars().map(u32::from));
+        for &b in chunk.invalid() {
+            if !marked {
+                wide.push(MAPPED_STRING_MARK);
+                marked = true;
+            }
+            wide.push(MAP_AREA_START + b as u32);
+        }
+    }
+    Ok(WideCString::from_vec(wide)?)
+}
+
+/// the path named by `wide`, undoing the mapping of [`construct`]
+#[cfg(not(unix))]
+pub(crate) fn from_wide(wide: &RarStr) -> PathBuf {
+    PathBuf::from(wide.to_os_string())
+}
+
+/// the path named by `wide`, undoing the mapping of [`construct`]
+#[cfg(unix)]
+pub(crate) fn from_wide(wide: &RarStr) -> PathBuf {
+    use std::os::unix::ffi::OsStringExt;
+    let wide = wide.as_slice();
+    if !wide.contains(&MAPPED_STRING_MARK) {
+        return PathBuf::from(wide_to_string(wide));
+    }
+    let mut bytes = Vec::with_capacity(wide.len());
+    for &c in wide {
+        match c {
+            MAPPED_STRING_MARK => {}
+            c if (MAP_AREA_START + 0x80..MAP_AREA_START + 0x100).contains(&c) => {
+                bytes.push((c - MAP_AREA_START) as u8)
+            }
+            c => bytes.extend_from_slice(wide_to_string(&[c]).as_bytes()),
+        }
+    }
+    PathBuf::from(std::ffi::OsString::from_vec(bytes))
+}
+
+#[cfg(unix)]
+fn wide_to_string(wide: &[u32]) -> String {
+    widestring::U32Str::from_slice(wide).to_string_lossy()
+}
+
+pub(crate) fn process_file(
+    handle: *const unrar_sys::Handle,
+    operation: i32,
+    dest_path: Option<&RarStr>,
+    dest_name: Option<&RarStr>,
+) -> i32 {
+    unsafe {
+        unrar_sys::RARProcessFileW(
+            handle,
+            operation,
+            dest_path
+                .map(|path| path.as_ptr().cast())
+                .unwrap_or(std::ptr::null()),
+            dest_name
+                .map(|file| file.as_ptr().cast())
+                .unwrap_or(std::ptr::null()),
+        )
+    }
+}
+
+/// Runs `f`, which calls into the DLL, with the current thread in a UTF-8 locale.
+///
+/// unrar converts wide strings through the C library, which in the default C locale cannot
+/// represent anything beyond ASCII, so names would end up with underscores. Other threads
+/// are not affected, and if no UTF-8 locale is installed `f` runs as is.
+#[cfg(target_os = "linux")]
+pub(crate) fn in_utf8_locale<T>(f: impl FnOnce() -> T) -> T {
+    use std::sync::OnceLock;
+    // the locale is never freed, `locale_t` is kept as an address to be shared by threads
+    static UTF8: OnceLock<usize> = OnceLock::new();
+    let utf8 = *UTF8.get_or_init(|| {
+        [c"C.UTF-8", c"en_US.UTF-8"]
+            .iter()
+            .map(|name| unsafe {
+                libc::newlocale(libc::LC_CTYPE_MASK, name.as_ptr(), std::ptr::null_mut())
+            })
+            .find(|locale| !locale.is_null())
+            .map_or(0, |locale| locale as usize)
+    });
+    if utf8 == 0 {
+        return f();
+    }
+    let previous = unsafe { libc::uselocale(utf8 as libc::locale_t) };
+    let result = f();
+    unsafe { libc::uselocale(previous) };
+    result
+}
+
+/// Runs `f` as is: there is no per-thread locale to switch to here, and on Windows and macOS
+/// unrar converts wide strings without the locale anyway.
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn in_utf8_locale<T>(f: impl FnOnce() -> T) -> T {
+    f()
+}
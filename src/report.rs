@@ -0,0 +1,108 @@
+use crate::error::{Code, UnrarError, UnrarResult};
+use crate::open_archive::{CursorBeforeHeader, FileHeader, OpenArchive, Process};
+use std::path::Path;
+
+/// The outcome of testing every entry of an archive, see
+/// [`Archive::test_all`](crate::Archive::test_all).
+///
+/// Unlike [`OpenArchive::test`], which gives up the archive along with the first error,
+/// testing goes on past entries whose data turns out damaged or cannot be decrypted, so one
+/// broken entry does not hide the state of all others. Errors after which unrar cannot read
+/// any further, like damaged headers or missing volumes, end testing early.
+///
+/// # Example
+///
+/// ```
+/// use unrar::Archive;
+/// use unrar::error::Code;
+///
+/// let report = Archive::new("data/bad-crc.rar").test_all().unwrap();
+/// assert!(!report.is_ok());
+/// let (entry, error) = report.failed().next().unwrap();
+/// assert_eq!(entry.filename.to_str(), Some("bad.txt"));
+/// assert_eq!(error.code, Code::BadData);
+/// ```
+#[derive(Debug)]
+pub struct TestReport {
+    /// the entries tested, in the order of the archive, each with its outcome. Continuations
+    /// of split entries are tested along with the entry they continue and are left out.
+    pub entries: Vec<(FileHeader, UnrarResult<()>)>,
+    /// the error that ended testing before the end of the archive, if any. If it occurred
+    /// while processing an entry, that entry is not part of `entries` but the error
+    /// carries its [`filename`](UnrarError::filename).
+    pub error: Option<UnrarError>,
+}
+
+impl TestReport {
+    /// did all entries pass, with the whole archive tested
+    pub fn is_ok(&self) -> bool {
+        self.error.is_none() && self.entries.iter().all(|(_, result)| result.is_ok())
+    }
+
+    /// the entries that failed along with their errors
+    pub fn failed(&self) -> impl Iterator<Item = (&FileHeader, &UnrarError)> {
+        self.entries
+            .iter()
+            .filter_map(|(entry, result)| Some((entry, result.as_ref().err()?)))
+    }
+}
+
+/// Tests every entry of `archive` or, given `base`, extracts them into `base`, going on past
+/// errors confined to a single entry.
+pub(crate) fn run(
+    mut archive: OpenArchive<Process, CursorBeforeHeader>,
+    base: Option<&Path>,
+) -> TestReport {
+    let mut report = TestReport {
+        entries: Vec::new(),
+        error: None,
+    };
+    loop {
+        let header = match archive.read_header() {
+            Ok(Some(header)) => header,
+            Ok(None) => break,
+            Err(error) => {
+                report.error = Some(error);
+                break;
+            }
+        };
+        let entry = header.entry().clone();
+        // continuations of split entries were processed along with the entry itself
+        let (result, rest) = match entry.is_split_before() {
+            true => match header.skip() {
+                Ok(rest) => (Ok(()), rest),
+                Err(error) => {
+                    report.error = Some(error);
+                    break;
+                }
+            },
+            false => header.test_then(base),
+        };
+        match result {
+            Err(error) if !goes_on(&error) => {
+                report.error = Some(error);
+                break;
+            }
+            result if !entry.is_split_before() => report.entries.push((entry, result)),
+            _ => {}
+        }
+        archive = rest;
+    }
+    report
+}
+
+/// Can unrar go on with the next entry after `error`? It can if it has left the data of
+/// the entry behind, which it does for damaged data, wrong passwords and failures of the
+/// file it extracts to. Others, like missing volumes or passwords or aborted processing,
+/// leave it in the middle of the data.
+fn goes_on(error: &UnrarError) -> bool {
+    matches!(
+        error.code,
+        Code::BadData
+            | Code::BadPassword
+            | Code::ECreate
+            | Code::EWrite
+            | Code::EClose
+            | Code::EReference
+    )
+}
@@ -0,0 +1,778 @@
+use crate::error::{Code, UnrarError, UnrarResult, When};
+use crate::format::{self, Block15, Block50, MainHeader, RarFormat, Reader};
+use crate::open_archive::{EntryFlags, EntryKind, FileHeader, Link, LinkKind};
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+/// the name of service headers holding NTFS alternate data streams
+const STREAM: &[u8] = b"STM";
+/// `MAX_HEADER_SIZE_RAR5` in unrar
+const MAX_HEADER_SIZE: u64 = 0x200000;
+/// the FILETIME of the Unix epoch
+const UNIX_EPOCH_TICKS: i64 = 116_444_736_000_000_000;
+/// `INT64NDF` in unrar, reported as the unpacked size if it is unknown
+const UNKNOWN_SIZE: u64 = 0x7fffffff7fffffff;
+
+/// Options for [`Archive::list`](crate::Archive::list).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ListOptions {
+    /// also list the service headers which follow the files they belong to, like NTFS
+    /// alternate data streams, see [`EntryKind`].
+    ///
+    /// unrar skips service headers while listing, so the headers are read again without it,
+    /// which is not possible for archives with encrypted headers.
+    pub include_services: bool,
+}
+
+/// How [`Archive::extract_streams`](crate::Archive::extract_streams) writes NTFS alternate
+/// data streams, which Linux file systems do not have.
+#[cfg(target_os = "linux")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamTarget {
+    /// as the extended attribute `user.<name>` of the file the stream belongs to
+    Xattr,
+    /// as the file `<parent>:<name>` next to the file the stream belongs to, like Windows
+    /// names streams
+    Sidecar,
+}
+
+/// A header of an archive, as far as placing the service headers among the entries the
+/// DLL lists and locating the data of entries is concerned.
+pub(crate) enum Header {
+    /// a file header
+    File(Box<Part>),
+    /// a service header, which the DLL skips
+    Service(Box<Service>),
+}
+
+/// A file header, read without the DLL.
+pub(crate) struct Part {
+    /// the header, whose name is only right for ASCII names in RAR 1.5 to 4.x archives
+    pub(crate) header: FileHeader,
+    /// does the header continue an entry from the previous volume, which the DLL does not
+    /// list
+    pub(crate) split_before: bool,
+    /// does the header store a check value of the password, which RAR 5.0 headers may and
+    /// older ones never do
+    pub(crate) password_check: bool,
+    pub(crate) data: Data,
+}
+
+/// A service header, read without the DLL.
+pub(crate) struct Service {
+    /// the header, named like the service
+    header: FileHeader,
+    /// the name of the stream for stream headers, with the leading colon
+    stream: Option<String>,
+    data: Data,
+}
+
+/// Where the data of a service header is.
+pub(crate) struct Data {
+    pub(crate) volume: PathBuf,
+    pub(crate) position: u64,
+    /// the CRC of the data, which RAR 5.0 may replace by a BLAKE2 hash
+    crc: Option<u32>,
+}
+
+/// reads the headers of the archive starting at `path` up to the first one that cannot be
+/// read, where `next_volume` names the volume after the given one.
+///
+/// Archives with encrypted headers have no readable headers.
+pub(crate) fn read(path: &Path, next_volume: impl Fn(&Path) -> Option<PathBuf>) -> Vec<Header> {
+    let mut headers = Vec::new();
+    let mut volume = path.to_path_buf();
+    while let Some(true) = read_volume(&volume, &mut headers) {
+        match next_volume(&volume) {
+            Some(next) => volume = next,
+            None => break,
+        }
+    }
+    headers
+}
+
+/// the packed size of the part the volume at `volume` continues a split entry with, if its
+/// headers can be read
+pub(crate) fn continued_size(volume: &Path) -> Option<u64> {
+    let mut headers = Vec::new();
+    read_volume(volume, &mut headers);
+    headers
+        .into_iter()
+        .find_map(|header| match header {
+            Header::File(part) => Some(part),
+            Header::Service(_) => None,
+        })
+        .filter(|part| part.split_before)
+        .map(|part| part.header.packed_size)
+}
+
+/// the entries of the archive starting at `path` whose headers store a check value of the
+/// password, where `next_volume` names the volume after the given one
+pub(crate) fn password_checks(
+    path: &Path,
+    next_volume: impl Fn(&Path) -> Option<PathBuf>,
+) -> Vec<FileHeader> {
+    read(path, next_volume)
+        .into_iter()
+        .filter_map(|header| match header {
+            Header::File(part) if !part.split_before && part.password_check => Some(part.header),
+            _ => None,
+        })
+        .collect()
+}
+
+/// reads the headers of a single volume into `headers` and returns whether another volume
+/// follows
+fn read_volume(volume: &Path, headers: &mut Vec<Header>) -> Option<bool> {
+    let main = MainHeader::read(volume).ok()?;
+    let mut file = File::open(volume).ok()?;
+    match main.format.version {
+        RarFormat::Rar15to4 => read_volume15(volume, &mut file, main.format.offset + 7, headers),
+        RarFormat::Rar5 | RarFormat::Rar7 => {
+            read_volume50(volume, &mut file, main.format.offset + 8, headers)
+        }
+        RarFormat::Rar14 => None,
+    }
+}
+
+fn read_volume15(
+    volume: &Path,
+    file: &mut File,
+    mut position: u64,
+    headers: &mut Vec<Header>,
+) -> Option<bool> {
+    const MAIN: u8 = 0x73;
+    const FILE: u8 = 0x74;
+    const SERVICE: u8 = 0x7a;
+    const END: u8 = 0x7b;
+    const MHD_PASSWORD: u16 = 0x80;
+    const LHD_SPLIT_BEFORE: u16 = 0x1;
+    const EARC_NEXT_VOLUME: u16 = 0x1;
+
+    loop {
+        file.seek(SeekFrom::Start(position)).ok()?;
+        let mut header = vec![0; 7];
+        file.read_exact(&mut header).ok()?;
+        let size = u16::from_le_bytes([header[5], header[6]]) as usize;
+        if size > header.len() {
+            header.resize(size, 0);
+            file.read_exact(&mut header[7..]).ok()?;
+        }
+        let (block, _) = Block15::parse(&header)?;
+        if !block.intact {
+            return None;
+        }
+        let data = position.checked_add(block.size as u64)?;
+        match block.kind {
+            MAIN if block.flags & MHD_PASSWORD != 0 => return None,
+            FILE => {
+                let (header, _) = header15(&header, &block)?;
+                headers.push(Header::File(Box::new(Part {
+                    data: Data {
+                        volume: volume.to_path_buf(),
+                        position: data,
+                        crc: Some(header.file_crc),
+                    },
+                    header,
+                    split_before: block.flags & LHD_SPLIT_BEFORE != 0,
+                    password_check: false,
+                })));
+            }
+            SERVICE if block.flags & LHD_SPLIT_BEFORE == 0 => {
+                let (header, stream) = header15(&header, &block)?;
+                headers.push(Header::Service(Box::new(Service {
+                    data: Data {
+                        volume: volume.to_path_buf(),
+                        position: data,
+                        crc: Some(header.file_crc),
+                    },
+                    header,
+                    stream,
+                })));
+            }
+            END => return Some(block.flags & EARC_NEXT_VOLUME != 0),
+            _ => {}
+        }
+        position = next(position, data, block.data_size)?;
+    }
+}
+
+/// the position of the header after the one at `position`, whose data starting at `data`
+/// is `data_size` bytes long. Sizes read from the archive may be anything, so `None` unless
+/// the position moves on without overflowing.
+fn next(position: u64, data: u64, data_size: u64) -> Option<u64> {
+    data.checked_add(data_size).filter(|&next| next > position)
+}
+
+/// converts a RAR 1.5 to 4.x file or service header the way the DLL reports file headers,
+/// along with the name of the stream for stream headers
+fn header15(header: &[u8], block: &Block15) -> Option<(FileHeader, Option<String>)> {
+    const LHD_SPLIT_AFTER: u16 = 0x2;
+    const LHD_PASSWORD: u16 = 0x4;
+    const LHD_SOLID: u16 = 0x10;
+    const LHD_LARGE: u16 = 0x100;
+    const LHD_SALT: u16 = 0x400;
+    const LHD_WINDOWMASK: u16 = 0xe0;
+    const LHD_DIRECTORY: u16 = 0xe0;
+    const HOST_UNIX: u8 = 3;
+    /// `SIZEOF_FILEHEAD3` in unrar
+    const FILE_HEAD_SIZE: usize = 32;
+
+    let mut body = Reader(&header[7..block.size]);
+    let packed_size = body.u32()? as u64;
+    let unpacked_size = body.u32()? as u64;
+    let host_os = body.u8()?;
+    let file_crc = body.u32()?;
+    let file_time = body.u32()?;
+    let _version = body.u8()?;
+    let method = body.u8()?;
+    let name_size = body.u16()? as usize;
+    let file_attr = body.u32()?;
+    let (packed_high, unpacked_high) = match block.flags & LHD_LARGE {
+        0 => (0, 0),
+        _ => (body.u32()? as u64, body.u32()? as u64),
+    };
+    let name = body.take(name_size)?;
+    // like unrar, the subdata follows the name and takes the rest of the header but the salt
+    let salt = match block.flags & LHD_SALT {
+        0 => 0,
+        _ => 8,
+    };
+    let size = block.size.saturating_sub(name_size + FILE_HEAD_SIZE + salt);
+    let subdata = body.0.get(..size).unwrap_or(body.0);
+
+    let mut flags = EntryFlags::empty();
+    flags.set(EntryFlags::SPLIT_AFTER, block.flags & LHD_SPLIT_AFTER != 0);
+    flags.set(EntryFlags::ENCRYPTED, block.flags & LHD_PASSWORD != 0);
+    flags.set(EntryFlags::SOLID, block.flags & LHD_SOLID != 0);
+    let directory = block.flags & LHD_WINDOWMASK == LHD_DIRECTORY;
+    flags.set(EntryFlags::DIRECTORY, directory);
+    let header = FileHeader {
+        filename: PathBuf::from(String::from_utf8_lossy(name).into_owned()),
+        flags,
+        unpacked_size: unpacked_high << 32 | unpacked_size,
+        packed_size: packed_high << 32 | packed_size,
+        file_crc,
+        file_time,
+        method: method as u32,
+        file_attr,
+        kind: EntryKind::File,
+        link: None,
+        modified: None,
+        unix: host_os == HOST_UNIX,
+        dict_size: match directory {
+            true => 0,
+            false => 0x10000 << ((block.flags & LHD_WINDOWMASK) >> 5),
+        },
+        raw_filename: name.split(|&b| b == 0).next().unwrap_or_default().to_vec(),
+    };
+    // like unrar's `RawToWide`, the stream name consists of little endian wide characters
+    let stream = (name == STREAM).then(|| {
+        let wide: Vec<u16> = subdata
+            .chunks_exact(2)
+            .map(|c| u16::from_le_bytes([c[0], c[1]]))
+            .take_while(|&c| c != 0)
+            .collect();
+        String::from_utf16_lossy(&wide)
+    });
+    Some((header, stream))
+}
+
+fn read_volume50(
+    volume: &Path,
+    file: &mut File,
+    mut position: u64,
+    headers: &mut Vec<Header>,
+) -> Option<bool> {
+    const FILE: u64 = 2;
+    const SERVICE: u64 = 3;
+    const ENCRYPTION: u64 = 4;
+    const END: u64 = 5;
+    const EHFL_NEXTVOLUME: u64 = 0x1;
+
+    loop {
+        let header = read_header(file, position)?;
+        let (block, _) = Block50::parse(&header)?;
+        if !block.intact {
+            return None;
+        }
+        let data = position.checked_add(header.len() as u64)?;
+        match block.kind {
+            ENCRYPTION => return None,
+            FILE => {
+                let crc = fields(&block)?.crc;
+                headers.push(Header::File(Box::new(Part {
+                    header: header50(&block, &mut crc.unwrap_or(0))?,
+                    split_before: block.split_before(),
+                    password_check: password_check(&block),
+                    data: Data {
+                        volume: volume.to_path_buf(),
+                        position: data,
+                        crc,
+                    },
+                })));
+            }
+            SERVICE if !block.split_before() => {
+                let crc = fields(&block)?.crc;
+                let stream = match service_name(&block)? {
+                    STREAM => block
+                        .subdata()
+                        .map(|name| String::from_utf8_lossy(name).into_owned()),
+                    _ => None,
+                };
+                headers.push(Header::Service(Box::new(Service {
+                    header: header50(&block, &mut crc.unwrap_or(0))?,
+                    stream,
+                    data: Data {
+                        volume: volume.to_path_buf(),
+                        position: data,
+                        crc,
+                    },
+                })));
+            }
+            END => return Some(Reader(block.body).vint()? & EHFL_NEXTVOLUME != 0),
+            _ => {}
+        }
+        position = next(position, data, block.data_size)?;
+    }
+}
+
+/// reads the header block at `position`, without its data
+fn read_header(file: &mut File, position: u64) -> Option<Vec<u8>> {
+    file.seek(SeekFrom::Start(position)).ok()?;
+    // the CRC and up to three bytes of the header size
+    let mut header = vec![0; 7];
+    file.read_exact(&mut header).ok()?;
+    let mut reader = Reader(&header[4..]);
+    let size = reader.vint()?;
+    if size > MAX_HEADER_SIZE {
+        return None;
+    }
+    let len = 7 - reader.0.len() + size as usize;
+    if len > header.len() {
+        let read = header.len();
+        header.resize(len, 0);
+        file.read_exact(&mut header[read..]).ok()?;
+    } else {
+        header.truncate(len);
+        file.seek(SeekFrom::Start(position + len as u64)).ok()?;
+    }
+    Some(header)
+}
+
+/// the name of a file or service header
+fn service_name<'a>(block: &Block50<'a>) -> Option<&'a [u8]> {
+    let mut body = fields(block)?.rest;
+    let size = usize::try_from(body.vint()?).ok()?;
+    body.take(size)
+}
+
+/// the fields of a file or service header up to the name, which follows them
+struct FileFields<'a> {
+    file_flags: u64,
+    unpacked_size: u64,
+    attributes: u64,
+    mtime: Option<u32>,
+    crc: Option<u32>,
+    compression: u64,
+    host_os: u64,
+    rest: Reader<'a>,
+}
+
+/// parses the fields of a file or service header
+fn fields<'a>(block: &Block50<'a>) -> Option<FileFields<'a>> {
+    const FHFL_UTIME: u64 = 0x2;
+    const FHFL_CRC32: u64 = 0x4;
+
+    let mut body = Reader(block.body);
+    let file_flags = body.vint()?;
+    let unpacked_size = body.vint()?;
+    let attributes = body.vint()?;
+    let mtime = match file_flags & FHFL_UTIME {
+        0 => None,
+        _ => Some(body.u32()?),
+    };
+    let crc = match file_flags & FHFL_CRC32 {
+        0 => None,
+        _ => Some(body.u32()?),
+    };
+    let compression = body.vint()?;
+    let host_os = body.vint()?;
+    Some(FileFields {
+        file_flags,
+        unpacked_size,
+        attributes,
+        mtime,
+        crc,
+        compression,
+        host_os,
+        rest: body,
+    })
+}
+
+/// does a file or service header have an encryption record
+fn encrypted(block: &Block50) -> bool {
+    const FHEXTRA_CRYPT: u64 = 0x1;
+    let mut encrypted = false;
+    block.extra(|kind, _| {
+        encrypted |= kind == FHEXTRA_CRYPT;
+        Some(())
+    });
+    encrypted
+}
+
+/// does a file or service header have an encryption record with a check value of the
+/// password
+fn password_check(block: &Block50) -> bool {
+    const FHEXTRA_CRYPT: u64 = 0x1;
+    const FHEXTRA_CRYPT_PSWCHECK: u64 = 0x1;
+    let mut check = false;
+    block.extra(|kind, mut record| {
+        if kind == FHEXTRA_CRYPT {
+            record.vint()?; // version
+            check = record.vint()? & FHEXTRA_CRYPT_PSWCHECK != 0;
+        }
+        Some(())
+    });
+    check
+}
+
+/// converts a RAR 5.0 file or service header the way the DLL reports file headers, where
+/// `crc` is the CRC of the last file, which unrar keeps reporting for entries without one
+fn header50(block: &Block50, crc: &mut u32) -> Option<FileHeader> {
+    const HFL_SPLITAFTER: u64 = 0x10;
+    const FHFL_DIRECTORY: u64 = 0x1;
+    const FHFL_UNPUNKNOWN: u64 = 0x8;
+    const FCI_SOLID: u64 = 0x40;
+    const HOST5_WINDOWS: u64 = 0;
+    const HOST5_UNIX: u64 = 1;
+    const FHEXTRA_HTIME: u64 = 0x3;
+    const FHEXTRA_VERSION: u64 = 0x4;
+    const FHEXTRA_REDIR: u64 = 0x5;
+    const FHEXTRA_REDIR_DIR: u64 = 0x1;
+    const FHEXTRA_HTIME_UNIXTIME: u64 = 0x1;
+    const FHEXTRA_HTIME_MTIME: u64 = 0x2;
+
+    let mut fields = fields(block)?;
+    let size = usize::try_from(fields.rest.vint()?).ok()?;
+    let mut name = String::from_utf8_lossy(fields.rest.take(size)?).into_owned();
+    // like unrar, the modification time of the header is overridden by the extra record
+    let mut mtime = fields
+        .mtime
+        .map(|mtime| mtime as i64 * 10_000_000 + UNIX_EPOCH_TICKS);
+    let mut link = None;
+    block.extra(|kind, mut record| {
+        match kind {
+            FHEXTRA_HTIME => {
+                let flags = record.vint()?;
+                if flags & FHEXTRA_HTIME_MTIME != 0 {
+                    mtime = Some(match flags & FHEXTRA_HTIME_UNIXTIME {
+                        0 => record.u64()? as i64,
+                        _ => record.u32()? as i64 * 10_000_000 + UNIX_EPOCH_TICKS,
+                    });
+                }
+            }
+            FHEXTRA_VERSION => {
+                record.vint()?;
+                let version = record.vint()?;
+                if version != 0 {
+                    name.push_str(&format!(";{version}"));
+                }
+            }
+            FHEXTRA_REDIR => {
+                let kind = LinkKind::from(record.vint()?);
+                let flags = record.vint()?;
+                let size = usize::try_from(record.vint()?).ok()?;
+                let target = String::from_utf8_lossy(record.take(size)?);
+                let target = target.split('\0').next().unwrap_or_default();
+                // like unrar, Windows gets its own separators
+                let target = match cfg!(windows) {
+                    true => target.replace('/', "\\"),
+                    false => target.to_owned(),
+                };
+                link = kind.filter(|_| size > 0).map(|kind| Link {
+                    kind,
+                    target: PathBuf::from(target),
+                    directory: flags & FHEXTRA_REDIR_DIR != 0,
+                });
+            }
+            _ => {}
+        }
+        Some(())
+    });
+
+    if let Some(file_crc) = fields.crc {
+        *crc = file_crc;
+    }
+    let mut flags = EntryFlags::empty();
+    flags.set(EntryFlags::SPLIT_AFTER, block.flags & HFL_SPLITAFTER != 0);
+    flags.set(EntryFlags::ENCRYPTED, encrypted(block));
+    flags.set(EntryFlags::SOLID, fields.compression & FCI_SOLID != 0);
+    flags.set(
+        EntryFlags::DIRECTORY,
+        fields.file_flags & FHFL_DIRECTORY != 0,
+    );
+    let filename = convert_name(name, fields.host_os == HOST5_WINDOWS);
+    // unrar hands out names of RAR 5.0 archives in UTF-8, at least on Unix
+    let raw_filename = filename.to_string_lossy().into_owned().into_bytes();
+    Some(FileHeader {
+        filename,
+        flags,
+        unpacked_size: match fields.file_flags & FHFL_UNPUNKNOWN {
+            0 => fields.unpacked_size,
+            _ => UNKNOWN_SIZE,
+        },
+        packed_size: block.data_size,
+        file_crc: *crc,
+        file_time: dos_time(mtime.unwrap_or(0)),
+        method: ((fields.compression >> 7) & 7) as u32 + 0x30,
+        file_attr: fields.attributes as u32,
+        kind: EntryKind::File,
+        link,
+        modified: mtime.and_then(|ticks| format::filetime(u64::try_from(ticks).ok()?)),
+        unix: fields.host_os == HOST5_UNIX,
+        dict_size: dictionary(fields.compression, flags.contains(EntryFlags::DIRECTORY)),
+        raw_filename,
+    })
+}
+
+/// the size of the dictionary like unrar's `ReadHeader50`, from the compression information
+fn dictionary(compression: u64, directory: bool) -> u64 {
+    let version = compression & 0x3f;
+    if directory || version > 1 {
+        return 0;
+    }
+    let mask = match version {
+        0 => 0x0f,
+        _ => 0x1f,
+    };
+    let size = 0x20000 << ((compression >> 10) & mask);
+    match version {
+        // RAR 7.0 allows for sizes between the powers of two
+        1 => size + size / 32 * ((compression >> 15) & 0x1f),
+        _ => size,
+    }
+}
+
+/// converts a name like unrar's `ConvertFileHeader`
+fn convert_name(name: String, windows: bool) -> PathBuf {
+    let name = name.split('\0').next().unwrap_or_default();
+    let name: String = name
+        .chars()
+        .map(|c| match c {
+            // backslashes are not separators in RAR 5.0, but invalid in names from Windows
+            '\\' if cfg!(windows) || windows => '_',
+            ':' if cfg!(windows) => '_',
+            '/' => std::path::MAIN_SEPARATOR,
+            c => c,
+        })
+        .collect();
+    PathBuf::from(name)
+}
+
+/// converts a FILETIME to local time in MS-DOS format like unrar's `RarTime::GetDos`
+fn dos_time(ticks: i64) -> u32 {
+    let Some((year, month, day, hour, minute, second)) = local_time(ticks) else {
+        return 0;
+    };
+    (second / 2)
+        | minute << 5
+        | hour << 11
+        | day << 16
+        | month << 21
+        | (year.wrapping_sub(1980)) << 25
+}
+
+#[cfg(unix)]
+fn local_time(ticks: i64) -> Option<(u32, u32, u32, u32, u32, u32)> {
+    let time = libc::time_t::try_from(ticks.checked_sub(UNIX_EPOCH_TICKS)? / 10_000_000).ok()?;
+    let mut tm = unsafe { std::mem::zeroed::<libc::tm>() };
+    if unsafe { libc::localtime_r(&time, &mut tm) }.is_null() {
+        return None;
+    }
+    Some((
+        (tm.tm_year + 1900) as u32,
+        (tm.tm_mon + 1) as u32,
+        tm.tm_mday as u32,
+        tm.tm_hour as u32,
+        tm.tm_min as u32,
+        tm.tm_sec as u32,
+    ))
+}
+
+#[cfg(windows)]
+fn local_time(ticks: i64) -> Option<(u32, u32, u32, u32, u32, u32)> {
+    use winapi::shared::minwindef::FILETIME;
+    use winapi::um::minwinbase::SYSTEMTIME;
+    use winapi::um::timezoneapi::{FileTimeToSystemTime, SystemTimeToTzSpecificLocalTime};
+
+    let time = FILETIME {
+        dwLowDateTime: ticks as u32,
+        dwHighDateTime: (ticks >> 32) as u32,
+    };
+    let mut utc = unsafe { std::mem::zeroed::<SYSTEMTIME>() };
+    let mut local = unsafe { std::mem::zeroed::<SYSTEMTIME>() };
+    let converted = unsafe {
+        FileTimeToSystemTime(&time, &mut utc) != 0
+            && SystemTimeToTzSpecificLocalTime(std::ptr::null(), &utc, &mut local) != 0
+    };
+    converted.then_some((
+        local.wYear as u32,
+        local.wMonth as u32,
+        local.wDay as u32,
+        local.wHour as u32,
+        local.wMinute as u32,
+        local.wSecond as u32,
+    ))
+}
+
+/// places the service headers read with [`read`] among the `entries` listed by the DLL.
+///
+/// Service headers following a file belong to it, which makes stream headers streams of
+/// the last file before them.
+pub(crate) fn insert(
+    entries: Vec<FileHeader>,
+    headers: Vec<Header>,
+) -> Vec<(FileHeader, Option<Data>)> {
+    let mut entries = entries.into_iter();
+    let mut listed = Vec::new();
+    let mut parent = None;
+    for header in headers {
+        match header {
+            Header::File(part) if part.split_before => {}
+            Header::File(_) => {
+                let Some(entry) = entries.next() else {
+                    break;
+                };
+                parent = Some(entry.filename.clone());
+                listed.push((entry, None));
+            }
+            Header::Service(service) => listed.push(service.into_entry(parent.as_deref())),
+        }
+    }
+    listed.extend(entries.map(|entry| (entry, None)));
+    listed
+}
+
+impl Service {
+    /// names the header after its kind, see [`EntryKind`]
+    fn into_entry(self, parent: Option<&Path>) -> (FileHeader, Option<Data>) {
+        let Service {
+            mut header,
+            stream,
+            data,
+        } = self;
+        let stream = stream.zip(parent).and_then(|(stream, parent)| {
+            let name = stream.strip_prefix(':')?.to_owned();
+            let mut filename = parent.as_os_str().to_owned();
+            filename.push(&stream);
+            Some((filename, parent.to_path_buf(), name))
+        });
+        header.kind = match stream {
+            Some((filename, parent, name)) => {
+                header.filename = PathBuf::from(filename);
+                EntryKind::Stream { parent, name }
+            }
+            None => EntryKind::Service {
+                name: header.filename.to_string_lossy().into_owned(),
+            },
+        };
+        (header, Some(data))
+    }
+}
+
+/// writes the stream `header` with its `data` below `base` as `target` says, returning
+/// `false` if its data is compressed, encrypted or split, which only unrar can read.
+///
+/// The file a stream belongs to is resolved below `base` like [`FsSink`](crate::FsSink)
+/// resolves entries, so it may neither lead out of `base` nor run through a symbolic link,
+/// and stream names must not hold separators, `..` or nul values.
+#[cfg(target_os = "linux")]
+pub(crate) fn write(
+    base: &Path,
+    header: &FileHeader,
+    data: &Data,
+    target: StreamTarget,
+) -> UnrarResult<bool> {
+    use std::io::Write;
+    use std::os::unix::ffi::OsStringExt;
+    const STORED: u32 = 0x30;
+
+    let EntryKind::Stream { parent, name } = &header.kind else {
+        return Ok(true);
+    };
+    if header.method != STORED || header.is_encrypted() || header.is_split() {
+        return Ok(false);
+    }
+    let error = |code, error| {
+        UnrarError::io(code, When::Process, error)
+            .with_filename(&header.filename)
+            .with_volume(&data.volume)
+    };
+    let code = match target {
+        StreamTarget::Xattr => Code::EWrite,
+        StreamTarget::Sidecar => Code::ECreate,
+    };
+    let invalid = |reason: &str| {
+        let reason = format!("{} {reason}", header.filename.display());
+        error(
+            code,
+            std::io::Error::new(std::io::ErrorKind::InvalidInput, reason),
+        )
+    };
+    if name.contains(['/', '\\', '\0']) || name.contains("..") {
+        return Err(invalid("has an invalid stream name"));
+    }
+    let path = crate::sink::resolve(base, parent).map_err(|e| error(code, e))?;
+    // a parent of `.` would put a sidecar next to `base` rather than below it
+    if path == base {
+        return Err(invalid(
+            "does not belong to a file below the target directory",
+        ));
+    }
+
+    let mut bytes = Vec::new();
+    let mut file = File::open(&data.volume).map_err(|e| error(Code::EOpen, e))?;
+    file.seek(SeekFrom::Start(data.position))
+        .and_then(|_| file.take(header.packed_size).read_to_end(&mut bytes))
+        .map_err(|e| error(Code::ERead, e))?;
+    if bytes.len() as u64 != header.packed_size
+        || data.crc.is_some_and(|crc| crc32fast::hash(&bytes) != crc)
+    {
+        return Err(UnrarError::from(Code::BadData, When::Process)
+            .with_filename(&header.filename)
+            .with_volume(&data.volume));
+    }
+    match target {
+        StreamTarget::Xattr => {
+            let path = std::ffi::CString::new(path.into_os_string().into_vec())
+                .map_err(|e| UnrarError::nul(e.into(), When::Process))?;
+            let name = std::ffi::CString::new(format!("user.{name}"))
+                .map_err(|e| UnrarError::nul(e.into(), When::Process))?;
+            // `lsetxattr` rather than `setxattr`, in case a symbolic link turned up since
+            // the path was resolved
+            let result = unsafe {
+                libc::lsetxattr(
+                    path.as_ptr(),
+                    name.as_ptr(),
+                    bytes.as_ptr().cast(),
+                    bytes.len(),
+                    0,
+                )
+            };
+            if result != 0 {
+                return Err(error(Code::EWrite, std::io::Error::last_os_error()));
+            }
+        }
+        StreamTarget::Sidecar => {
+            let mut sidecar = path.file_name().unwrap_or_default().to_owned();
+            sidecar.push(":");
+            sidecar.push(name);
+            crate::sink::create(&path.with_file_name(sidecar))
+                .and_then(|mut file| file.write_all(&bytes))
+                .map_err(|e| error(Code::ECreate, e))?;
+        }
+    }
+    Ok(true)
+}
@@ -0,0 +1,336 @@
+use crate::error::{Code, UnrarError, UnrarResult, When};
+use crate::memory::{MemoryEntry, MemoryNode, MemoryTree};
+use crate::open_archive::{CursorBeforeHeader, FileHeader, Link, LinkKind, OpenArchive, Process};
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Component, Path, PathBuf};
+
+/// A target to extract the entries of an archive to with
+/// [`Archive::extract_all`](crate::Archive::extract_all).
+///
+/// The entries are handed to the sink in the order they appear in the archive. Files are
+/// decompressed while they are written: their data arrives in chunks between
+/// [`begin_file`](Sink::begin_file) and [`end_file`](Sink::end_file), and unrar only
+/// verifies it once all of it has been written. Files that turn out to be damaged are
+/// handed to [`discard_file`](Sink::discard_file) instead of `end_file`.
+///
+/// An error returned by any of the methods stops the extraction.
+///
+/// # Example
+///
+/// ```
+/// use std::io;
+/// use unrar::{Archive, FileHeader, Link, Sink};
+///
+/// /// counts the bytes of all files
+/// #[derive(Default)]
+/// struct Count(usize);
+///
+/// impl Sink for Count {
+///     fn create_dir(&mut self, _: &FileHeader) -> io::Result<()> {
+///         Ok(())
+///     }
+///     fn begin_file(&mut self, _: &FileHeader) -> io::Result<()> {
+///         Ok(())
+///     }
+///     fn write_chunk(&mut self, data: &[u8]) -> io::Result<()> {
+///         self.0 += data.len();
+///         Ok(())
+///     }
+///     fn end_file(&mut self, _: &FileHeader) -> io::Result<()> {
+///         Ok(())
+///     }
+///     fn create_link(&mut self, _: &FileHeader, _: &Link) -> io::Result<()> {
+///         Ok(())
+///     }
+/// }
+///
+/// let mut count = Count::default();
+/// Archive::new("data/version.rar").extract_all(&mut count).unwrap();
+/// assert_eq!(count.0, 11);
+/// ```
+pub trait Sink {
+    /// creates the directory `header`
+    fn create_dir(&mut self, header: &FileHeader) -> io::Result<()>;
+
+    /// starts the file `header`, whose data is handed to
+    /// [`write_chunk`](Sink::write_chunk) next
+    fn begin_file(&mut self, header: &FileHeader) -> io::Result<()>;
+
+    /// writes the next chunk of data of the file started last
+    fn write_chunk(&mut self, data: &[u8]) -> io::Result<()>;
+
+    /// finishes the file started last, all of whose data was written and found intact.
+    /// `header` holds its metadata, like the modification time and attributes.
+    fn end_file(&mut self, header: &FileHeader) -> io::Result<()>;
+
+    /// drops the file started last, whose data could not be read completely or was damaged
+    fn discard_file(&mut self, header: &FileHeader) -> io::Result<()> {
+        let _ = header;
+        Ok(())
+    }
+
+    /// creates the entry `header`, which is a link
+    fn create_link(&mut self, header: &FileHeader, link: &Link) -> io::Result<()>;
+}
+
+/// hands every entry of `archive` to `sink`
+pub(crate) fn extract(
+    mut archive: OpenArchive<Process, CursorBeforeHeader>,
+    sink: &mut dyn Sink,
+) -> UnrarResult<()> {
+    while let Some(header) = archive.read_header()? {
+        let entry = header.entry().clone();
+        let failed = |code| {
+            let filename = entry.filename.clone();
+            move |error| UnrarError::io(code, When::Process, error).with_filename(&filename)
+        };
+        archive = match (entry.is_directory(), entry.link()) {
+            // continuations of split entries were read along with the entry itself
+            _ if entry.is_split_before() => header.skip()?,
+            (true, _) => {
+                sink.create_dir(&entry).map_err(failed(Code::ECreate))?;
+                header.skip()?
+            }
+            (false, Some(link)) => {
+                sink.create_link(&entry, link)
+                    .map_err(failed(Code::ECreate))?;
+                header.skip()?
+            }
+            (false, None) => {
+                sink.begin_file(&entry).map_err(failed(Code::ECreate))?;
+                match header.read_to_sink(sink) {
+                    Ok(rest) => {
+                        sink.end_file(&entry).map_err(failed(Code::EClose))?;
+                        rest
+                    }
+                    Err(error) => {
+                        // the original error is more telling than one from discarding
+                        let _ = sink.discard_file(&entry);
+                        return Err(error);
+                    }
+                }
+            }
+        };
+    }
+    Ok(())
+}
+
+/// A [`Sink`] writing the entries below a directory.
+///
+/// Entries are only written below the directory: paths that are absolute or lead out of
+/// it are rejected, and so are paths running through or ending in a symbolic link, which
+/// an earlier entry may have created to point elsewhere. Symbolic links are only created
+/// if their target stays below the directory without running through another one, and
+/// hard links and file copies are made from earlier entries.
+///
+/// Only the contents are written, not the timestamps or attributes of the entries.
+/// Symbolic links of RAR 4.x archives cannot be created, since unrar does not tell their
+/// target.
+#[derive(Debug)]
+pub struct FsSink {
+    base: PathBuf,
+    file: Option<(PathBuf, File)>,
+}
+
+impl FsSink {
+    /// writes the entries below `base`, which is created if needed
+    pub fn new<P: AsRef<Path>>(base: P) -> Self {
+        FsSink {
+            base: base.as_ref().to_path_buf(),
+            file: None,
+        }
+    }
+
+    /// The path below the base directory where the entry `name` goes, see [`resolve`].
+    fn path(&self, name: &Path) -> io::Result<PathBuf> {
+        resolve(&self.base, name)
+    }
+
+    /// creates the parent directories of `path`
+    fn create_parent(path: &Path) -> io::Result<()> {
+        match path.parent() {
+            Some(parent) => fs::create_dir_all(parent),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Sink for FsSink {
+    fn create_dir(&mut self, header: &FileHeader) -> io::Result<()> {
+        fs::create_dir_all(self.path(&header.filename)?)
+    }
+
+    fn begin_file(&mut self, header: &FileHeader) -> io::Result<()> {
+        let path = self.path(&header.filename)?;
+        Self::create_parent(&path)?;
+        self.file = Some((path.clone(), create(&path)?));
+        Ok(())
+    }
+
+    fn write_chunk(&mut self, data: &[u8]) -> io::Result<()> {
+        match &mut self.file {
+            Some((_, file)) => file.write_all(data),
+            None => Err(io::Error::other("no file was started")),
+        }
+    }
+
+    fn end_file(&mut self, _: &FileHeader) -> io::Result<()> {
+        self.file = None;
+        Ok(())
+    }
+
+    fn discard_file(&mut self, _: &FileHeader) -> io::Result<()> {
+        match self.file.take() {
+            Some((path, file)) => {
+                drop(file);
+                fs::remove_file(path)
+            }
+            None => Ok(()),
+        }
+    }
+
+    fn create_link(&mut self, header: &FileHeader, link: &Link) -> io::Result<()> {
+        let path = self.path(&header.filename)?;
+        Self::create_parent(&path)?;
+        match link.kind {
+            LinkKind::Hardlink => fs::hard_link(self.path(&link.target)?, path),
+            LinkKind::FileCopy => fs::copy(self.path(&link.target)?, path).map(|_| ()),
+            _ if link.target.as_os_str().is_empty() => Err(unknown_target()),
+            _ => {
+                // resolve the target from the directory of the link, as the OS would
+                let parent = header.filename.parent().unwrap_or(Path::new(""));
+                self.path(&parent.join(&link.target))?;
+                symlink(&link.target, &path, link.directory)
+            }
+        }
+    }
+}
+
+/// The path below `base` where the entry `name` goes.
+///
+/// The components are walked on the file system rather than on the text of `name`, since a
+/// symbolic link makes `..` lead elsewhere than the text says. So none of them, the last
+/// one included, may be a symbolic link, and `..` may only lead out of directories that
+/// exist already.
+pub(crate) fn resolve(base: &Path, name: &Path) -> io::Result<PathBuf> {
+    let invalid = |reason: &str| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{} {reason}", name.display()),
+        )
+    };
+    let mut path = base.to_path_buf();
+    // the components below the base, each known to be a directory if one is followed by
+    // `..`
+    let mut depth = 0;
+    for component in name.components() {
+        match component {
+            Component::Normal(component) => {
+                path.push(component);
+                depth += 1;
+                if fs::symlink_metadata(&path).is_ok_and(|m| m.is_symlink()) {
+                    return Err(invalid("runs through a symbolic link"));
+                }
+            }
+            Component::CurDir => {}
+            Component::ParentDir if depth > 0 => {
+                if !fs::symlink_metadata(&path).is_ok_and(|m| m.is_dir()) {
+                    return Err(invalid("leads out of a directory that does not exist"));
+                }
+                path.pop();
+                depth -= 1;
+            }
+            _ => return Err(invalid("leads out of the target directory")),
+        }
+    }
+    Ok(path)
+}
+
+/// the error for symbolic links of RAR 4.x archives, whose target unrar does not tell
+pub(crate) fn unknown_target() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Unsupported,
+        "the target of the symbolic link is unknown",
+    )
+}
+
+/// creates or truncates the file at `path`, refusing to follow a symbolic link at `path`
+/// that turned up since it was checked
+pub(crate) fn create(path: &Path) -> io::Result<File> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::custom_flags(&mut options, libc::O_NOFOLLOW);
+    options.open(path)
+}
+
+#[cfg(unix)]
+fn symlink(target: &Path, path: &Path, _directory: bool) -> io::Result<()> {
+    std::os::unix::fs::symlink(target, path)
+}
+
+#[cfg(windows)]
+fn symlink(target: &Path, path: &Path, directory: bool) -> io::Result<()> {
+    match directory {
+        true => std::os::windows::fs::symlink_dir(target, path),
+        false => std::os::windows::fs::symlink_file(target, path),
+    }
+}
+
+/// A [`Sink`] collecting the entries into a [`MemoryTree`].
+#[derive(Debug, Default)]
+pub struct MemorySink {
+    tree: MemoryTree,
+    file: Vec<u8>,
+}
+
+impl MemorySink {
+    /// creates a sink with an empty tree
+    pub fn new() -> Self {
+        MemorySink::default()
+    }
+
+    /// the entries collected so far
+    pub fn into_tree(self) -> MemoryTree {
+        self.tree
+    }
+
+    fn insert(&mut self, header: &FileHeader, node: MemoryNode) -> io::Result<()> {
+        let entry = MemoryEntry {
+            header: header.clone(),
+            node,
+        };
+        self.tree.insert(entry)
+    }
+}
+
+impl Sink for MemorySink {
+    fn create_dir(&mut self, header: &FileHeader) -> io::Result<()> {
+        self.insert(header, MemoryNode::Directory)
+    }
+
+    fn begin_file(&mut self, _: &FileHeader) -> io::Result<()> {
+        self.file.clear();
+        Ok(())
+    }
+
+    fn write_chunk(&mut self, data: &[u8]) -> io::Result<()> {
+        self.file.extend_from_slice(data);
+        Ok(())
+    }
+
+    fn end_file(&mut self, header: &FileHeader) -> io::Result<()> {
+        let data = std::mem::take(&mut self.file);
+        self.insert(header, MemoryNode::File(data))
+    }
+
+    fn discard_file(&mut self, _: &FileHeader) -> io::Result<()> {
+        self.file.clear();
+        Ok(())
+    }
+
+    fn create_link(&mut self, header: &FileHeader, link: &Link) -> io::Result<()> {
+        self.insert(header, MemoryNode::Link(link.clone()))
+    }
+}
@@ -0,0 +1,187 @@
+//! Reading stored entries straight from the volumes, see [`OpenArchive::open_seekable`].
+//!
+//! [`OpenArchive::open_seekable`]: crate::OpenArchive::open_seekable
+
+use crate::archive::next_part;
+use crate::error::{Code, UnrarError, UnrarResult, When};
+use crate::open_archive::FileHeader;
+use crate::services::{self, Header, Part};
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+/// the method of entries whose data is not compressed
+const STORED: u32 = 0x30;
+
+/// A reader of a stored entry, i.e. one whose data is neither compressed nor encrypted,
+/// which reads the data straight from the volumes and can seek anywhere within it.
+///
+/// See [`OpenArchive::open_seekable`](crate::OpenArchive::open_seekable). The volumes are
+/// opened as the data is read, one at a time. Unlike unrar, the reader does not check the
+/// CRC of the data.
+#[derive(Debug)]
+pub struct StoredReader {
+    segments: Vec<Segment>,
+    size: u64,
+    position: u64,
+    /// the segment that was read last along with its open volume
+    file: Option<(usize, File)>,
+}
+
+/// a part of the data that lies in a single volume
+#[derive(Debug)]
+struct Segment {
+    volume: PathBuf,
+    /// where the part starts in the volume
+    offset: u64,
+    /// where the part starts in the data
+    start: u64,
+    len: u64,
+}
+
+impl StoredReader {
+    /// the size of the data
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// the segment holding the byte at `position`, which must be less than the size
+    fn segment(&self, position: u64) -> usize {
+        self.segments
+            .partition_point(|segment| segment.start + segment.len <= position)
+    }
+}
+
+impl Read for StoredReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() || self.position >= self.size {
+            return Ok(0);
+        }
+        let index = self.segment(self.position);
+        let segment = &self.segments[index];
+        let file = match &mut self.file {
+            Some((open, file)) if *open == index => file,
+            file => &mut file.insert((index, File::open(&segment.volume)?)).1,
+        };
+        let within = self.position - segment.start;
+        file.seek(SeekFrom::Start(segment.offset + within))?;
+        let len = buf.len().min((segment.len - within) as usize);
+        let read = file.read(&mut buf[..len])?;
+        if read == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "the volume is truncated",
+            ));
+        }
+        self.position += read as u64;
+        Ok(read)
+    }
+}
+
+impl Seek for StoredReader {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let position = match pos {
+            SeekFrom::Start(position) => Some(position),
+            SeekFrom::End(offset) => self.size.checked_add_signed(offset),
+            SeekFrom::Current(offset) => self.position.checked_add_signed(offset),
+        };
+        let Some(position) = position else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            ));
+        };
+        self.position = position;
+        Ok(position)
+    }
+}
+
+/// Opens the data of `entry`, the `ordinal`-th entry the DLL lists of the archive starting
+/// at `path`. Returns `None` if the headers of the archive cannot be read that far.
+pub(crate) fn open(
+    path: &Path,
+    ordinal: usize,
+    entry: &FileHeader,
+) -> UnrarResult<Option<StoredReader>> {
+    let mut parts = services::read(path, next_part)
+        .into_iter()
+        .filter_map(|header| match header {
+            Header::File(part) => Some(*part),
+            Header::Service(_) => None,
+        });
+    // the DLL does not list headers continuing an entry, so only the others are counted
+    let first = parts
+        .by_ref()
+        .filter(|part| !part.split_before)
+        .nth(ordinal);
+    let Some(first) = first else {
+        return Ok(None);
+    };
+
+    // the headers do not match the entry the DLL listed
+    let mismatch = || UnrarError::from(Code::BadData, When::Read).with_filename(&entry.filename);
+    // every part records the size of the whole entry, but only one that is not split the
+    // CRC of the whole entry
+    if first.header.unpacked_size != entry.unpacked_size
+        || (!first.header.is_split_after() && first.header.file_crc != entry.file_crc)
+    {
+        return Err(mismatch());
+    }
+
+    let mut segments = Vec::new();
+    let mut size: u64 = 0;
+    let mut part = first;
+    loop {
+        check(&part, entry)?;
+        segments.push(Segment {
+            volume: part.data.volume.clone(),
+            offset: part.data.position,
+            start: size,
+            len: part.header.packed_size,
+        });
+        size = size
+            .checked_add(part.header.packed_size)
+            .filter(|&size| size <= entry.unpacked_size)
+            .ok_or_else(mismatch)?;
+        if !part.header.is_split_after() {
+            break;
+        }
+        part = match parts.next().filter(|next| next.split_before) {
+            Some(next) => next,
+            None => {
+                let error =
+                    UnrarError::from(Code::EOpen, When::Process).with_filename(&entry.filename);
+                return Err(match next_part(&part.data.volume) {
+                    Some(volume) => error.with_volume(&volume),
+                    None => error,
+                });
+            }
+        };
+    }
+    if size != entry.unpacked_size {
+        return Err(mismatch());
+    }
+    Ok(Some(StoredReader {
+        segments,
+        size,
+        position: 0,
+        file: None,
+    }))
+}
+
+/// fails with [`Code::NotStored`] unless the data of `part` can be read as is
+fn check(part: &Part, entry: &FileHeader) -> UnrarResult<()> {
+    match is_stored(&part.header) {
+        true => Ok(()),
+        false => Err(not_stored(entry)),
+    }
+}
+
+/// is the data of `header` neither compressed nor encrypted
+pub(crate) fn is_stored(header: &FileHeader) -> bool {
+    header.method == STORED && !header.is_encrypted()
+}
+
+pub(crate) fn not_stored(entry: &FileHeader) -> UnrarError {
+    UnrarError::from(Code::NotStored, When::Process).with_filename(&entry.filename)
+}
@@ -57,3 +57,20 @@ fn solid() {
     let archive = Archive::new("data/version.rar").open_for_listing().unwrap();
     assert!(!archive.is_solid());
 }
+
+#[test]
+fn signed() {
+    let archive = Archive::new("data/signed.rar").open_for_listing().unwrap();
+    assert!(archive.is_signed());
+    let signature = archive.signature().unwrap();
+    assert_eq!(signature.creator.as_deref(), Some("Test User"));
+    assert_eq!(signature.archive_name.as_deref(), Some("signed.rar"));
+    assert_eq!(signature.creation_time, Some(0x5a8e3100));
+    // the AV block followed by the sign block
+    assert_eq!(signature.raw[2], 0x76);
+    assert_eq!(signature.raw[37], 0x79);
+
+    let archive = Archive::new("data/version.rar").open_for_listing().unwrap();
+    assert!(!archive.is_signed());
+    assert_eq!(archive.signature(), None);
+}
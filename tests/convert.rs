@@ -0,0 +1,103 @@
+#![cfg(feature = "convert")]
+
+use std::io::{Cursor, Read};
+use std::path::Path;
+use unrar::{convert, Archive};
+
+fn tar_entries(tar: &[u8]) -> Vec<(String, tar::EntryType, Vec<u8>)> {
+    tar::Archive::new(tar)
+        .entries()
+        .unwrap()
+        .map(|entry| {
+            let mut entry = entry.unwrap();
+            let path = entry.path().unwrap().to_string_lossy().into_owned();
+            let kind = entry.header().entry_type();
+            let mut data = Vec::new();
+            entry.read_to_end(&mut data).unwrap();
+            (path, kind, data)
+        })
+        .collect()
+}
+
+#[test]
+fn to_tar() {
+    let archive = Archive::new("data/multi.rar")
+        .open_for_processing()
+        .unwrap();
+    let tar = convert::to_tar(archive, Vec::new()).unwrap();
+    let entries = tar_entries(&tar);
+    let names: Vec<_> = entries.iter().map(|(name, _, _)| name.as_str()).collect();
+    assert_eq!(
+        names,
+        ["README.md", "src", "src/lib.rs", "src/util.rs", "VERSION"]
+    );
+    assert_eq!(entries[1].1, tar::EntryType::Directory);
+    assert_eq!(entries[4].1, tar::EntryType::Regular);
+    assert_eq!(entries[4].2, b"unrar-0.5.8");
+
+    let header = Archive::new("data/multi.rar")
+        .open_for_listing()
+        .unwrap()
+        .last()
+        .unwrap()
+        .unwrap();
+    let mut tar = tar::Archive::new(&tar[..]);
+    let last = tar.entries().unwrap().last().unwrap().unwrap();
+    let modified = header.modified().unwrap();
+    let secs = modified.duration_since(std::time::UNIX_EPOCH).unwrap();
+    assert_eq!(last.header().mtime().unwrap(), secs.as_secs());
+}
+
+#[test]
+fn to_tar_links() {
+    let archive = Archive::new("data/links.rar")
+        .open_for_processing()
+        .unwrap();
+    let tar = convert::to_tar(archive, Vec::new()).unwrap();
+    let mut tar = tar::Archive::new(&tar[..]);
+    let links: Vec<_> = tar
+        .entries()
+        .unwrap()
+        .map(|entry| {
+            let entry = entry.unwrap();
+            let kind = entry.header().entry_type();
+            let target = entry.link_name().unwrap().map(|t| t.into_owned());
+            (kind, target, entry.header().mode().unwrap())
+        })
+        .collect();
+    // archived on Unix, so the permissions are carried over
+    assert_eq!(links[0].2, 0o755);
+    assert_eq!(links[1].2, 0o644);
+    assert_eq!(links[2].0, tar::EntryType::Symlink);
+    assert_eq!(links[2].2, 0o777);
+    assert_eq!(links[2].1.as_deref(), Some(Path::new("file.txt")));
+    assert_eq!(links[3].0, tar::EntryType::Link);
+    assert_eq!(links[3].1.as_deref(), Some(Path::new("dir/file.txt")));
+}
+
+#[test]
+fn to_zip() {
+    let archive = Archive::new("data/links.rar")
+        .open_for_processing()
+        .unwrap();
+    let zip = convert::to_zip(archive, Cursor::new(Vec::new())).unwrap();
+    let mut zip = zip::ZipArchive::new(zip).unwrap();
+    let names: Vec<_> = zip.file_names().collect();
+    assert_eq!(names.len(), 4);
+    assert!(zip.by_name("dir/").unwrap().is_dir());
+    assert!(zip.by_name("dir/link").unwrap().is_symlink());
+    let mut data = String::new();
+    let mut hardlink = zip.by_name("hard.txt").unwrap();
+    hardlink.read_to_string(&mut data).unwrap();
+    assert_eq!(data, "linked file\n");
+}
+
+#[test]
+fn damaged() {
+    let archive = Archive::new("data/bad-crc.rar")
+        .open_for_processing()
+        .unwrap();
+    let err = convert::to_tar(archive, Vec::new()).unwrap_err();
+    assert_eq!(err.code, unrar::error::Code::BadData);
+    assert_eq!(err.filename().unwrap().to_str(), Some("bad.txt"));
+}
@@ -1,6 +1,6 @@
 use std::path::PathBuf;
 use unrar::error::{Code, When};
-use unrar::Archive;
+use unrar::{Archive, OpenOutcome, SecretPassword, UnrarResult};
 
 #[test]
 fn list() {
@@ -20,7 +20,7 @@ fn no_password() {
     let header = arc.read_header();
     assert!(matches!(header, Ok(Some(_))));
     let read_result = header.unwrap().unwrap().read();
-    assert!(matches!(read_result, Err(_)));
+    assert!(read_result.is_err());
     let err = read_result.unwrap_err();
     assert_eq!(err.code, Code::MissingPassword);
     assert_eq!(err.when, When::Process);
@@ -77,3 +77,179 @@ fn extract_encrypted_headers() {
     let s = String::from_utf8(bytes).unwrap();
     assert_eq!(s, "target\nCargo.lock\n");
 }
+
+/// reads the first entry of `archive`
+fn read_first(archive: Archive) -> UnrarResult<Vec<u8>> {
+    let header = archive.open_for_processing()?.read_header()?.unwrap();
+    Ok(header.read()?.0)
+}
+
+#[test]
+fn unicode_password() {
+    let path = "data/unicode-password.rar";
+    let data = read_first(Archive::with_password(path, "pässwörd✓")).unwrap();
+    assert_eq!(data, b"secret\n");
+
+    let utf16: Vec<u16> = "pässwörd✓".encode_utf16().collect();
+    let password = SecretPassword::from_utf16(&utf16).unwrap();
+    let data = read_first(Archive::with_secret_password(path, password)).unwrap();
+    assert_eq!(data, b"secret\n");
+
+    let password = SecretPassword::from(String::from("passwörd✓"));
+    let err = read_first(Archive::with_secret_password(path, password)).unwrap_err();
+    assert_eq!((err.code, err.when), (Code::BadPassword, When::Process));
+}
+
+#[test]
+fn secret_password() {
+    assert!(SecretPassword::from_utf16(&[0x70, 0xd800]).is_err());
+    assert_eq!(
+        format!("{:?}", SecretPassword::from("unrar")),
+        "SecretPassword(..)"
+    );
+
+    let password = SecretPassword::from_bytes(b"unrar");
+    let data = read_first(Archive::with_secret_password("data/crypted.rar", password)).unwrap();
+    assert_eq!(data, b"target\nCargo.lock\n");
+}
+
+#[test]
+fn password_not_utf8() {
+    // encrypted with the Latin-1 bytes of "päss", as unrar takes them
+    let path = "data/bytes-password.rar";
+    let password = SecretPassword::from_bytes(b"p\xe4ss");
+    let data = read_first(Archive::with_secret_password(path, password));
+    assert_eq!(data.unwrap(), b"secret\n");
+    // the password need not outlive the archive
+    let archive = Archive::with_password(path, &b"p\xe4ss".to_vec());
+    assert_eq!(read_first(archive).unwrap(), b"secret\n");
+
+    let password = SecretPassword::from("päss");
+    let err = read_first(Archive::with_secret_password(path, password));
+    assert_eq!(err.unwrap_err().code, Code::BadPassword);
+}
+
+#[test]
+fn check_password() {
+    let archive = Archive::new("data/crypted.rar");
+    assert!(archive.check_password("unrar").unwrap());
+    assert!(!archive.check_password("unrat").unwrap());
+
+    let archive = Archive::new("data/unicode-password.rar");
+    assert!(archive.check_password("pässwörd✓").unwrap());
+    assert!(!archive.check_password("pässwörd").unwrap());
+
+    // no check value of the password, so only the CRC tells
+    let archive = Archive::new("data/no-password-check.rar");
+    assert!(archive.check_password("password").unwrap());
+    assert!(!archive.check_password("passwort").unwrap());
+
+    // encrypted headers
+    let archive = Archive::new("data/comment-hpw-password.rar");
+    assert!(archive.check_password("password").unwrap());
+    assert!(!archive.check_password("passwort").unwrap());
+
+    // nothing encrypted
+    assert!(Archive::new("data/version.rar")
+        .check_password("anything")
+        .unwrap());
+
+    let err = Archive::new("data/crypted.rar")
+        .check_password("un\0rar")
+        .unwrap_err();
+    assert_eq!(err.code, Code::Nul);
+}
+
+#[test]
+fn try_passwords() {
+    let archive = Archive::new("data/crypted.rar");
+    assert_eq!(
+        archive.try_passwords(["", "unrat", "unrar"]).unwrap(),
+        Some(2)
+    );
+    assert_eq!(archive.try_passwords(["unrat", "UNRAR"]).unwrap(), None);
+    assert_eq!(archive.try_passwords(Vec::<String>::new()).unwrap(), None);
+
+    // opening needs the password
+    let archive = Archive::new("data/comment-hpw-password.rar");
+    let candidates = ["passwort", "password", "Password"];
+    assert_eq!(archive.try_passwords(candidates).unwrap(), Some(1));
+
+    let archive = Archive::new("data/unicode-password.rar");
+    let err = archive
+        .try_passwords(["wrong", "nu\0l", "pässwörd✓"])
+        .unwrap_err();
+    assert_eq!(err.code, Code::Nul);
+    let found = archive.try_passwords(["pässwörd✓", "nu\0l"]).unwrap();
+    assert_eq!(found, Some(0));
+
+    let err = Archive::new("data/missing.rar")
+        .try_passwords(["unrar"])
+        .unwrap_err();
+    assert_eq!(err.code, Code::EOpen);
+}
+
+#[test]
+fn try_passwords_parallel() {
+    let guesses = || (0..40).map(|n| format!("guess{n}"));
+    let archive = Archive::new("data/unicode-password.rar");
+    let candidates = guesses().chain(["pässwörd✓".into()]).chain(guesses());
+    assert_eq!(
+        archive.try_passwords_parallel(candidates, 4).unwrap(),
+        Some(40)
+    );
+    assert_eq!(archive.try_passwords_parallel(guesses(), 4).unwrap(), None);
+    assert_eq!(archive.try_passwords_parallel(guesses(), 0).unwrap(), None);
+
+    // the first fitting candidate wins even if a later one is found first
+    let candidates = guesses()
+        .chain(["pässwörd✓".into()])
+        .chain(["pässwörd✓".into()]);
+    assert_eq!(
+        archive.try_passwords_parallel(candidates, 8).unwrap(),
+        Some(40)
+    );
+
+    // an endless supply of candidates
+    let archive = Archive::new("data/comment-hpw-password.rar");
+    let candidates = ["password".to_string()]
+        .into_iter()
+        .chain(std::iter::repeat_with(String::new));
+    assert_eq!(
+        archive.try_passwords_parallel(candidates, 4).unwrap(),
+        Some(0)
+    );
+
+    // failures only count if no candidate before them fits
+    let candidates = guesses().chain(["nu\0l".into()]);
+    let err = archive.try_passwords_parallel(candidates, 4).unwrap_err();
+    assert_eq!(err.code, Code::Nul);
+    let candidates = ["password", "nu\0l"].map(String::from);
+    assert_eq!(
+        archive.try_passwords_parallel(candidates, 4).unwrap(),
+        Some(0)
+    );
+}
+
+#[test]
+fn probe() {
+    let probe = |path| Archive::new(path).probe().unwrap();
+    assert_eq!(
+        probe("data/comment-hpw-password.rar"),
+        OpenOutcome::NeedsPassword
+    );
+    // the password is not used, the headers are encrypted regardless
+    let archive = Archive::with_password("data/comment-hpw-password.rar", "password");
+    assert_eq!(archive.probe().unwrap(), OpenOutcome::NeedsPassword);
+
+    assert_eq!(probe("data/crypted.rar"), OpenOutcome::ContentsEncrypted);
+    assert_eq!(
+        probe("data/unicode-password.rar"),
+        OpenOutcome::ContentsEncrypted
+    );
+    assert_eq!(probe("data/version.rar"), OpenOutcome::NotEncrypted);
+    assert_eq!(probe("data/volumes.part1.rar"), OpenOutcome::NotEncrypted);
+
+    let err = Archive::new("data/missing.rar").probe().unwrap_err();
+    assert_eq!(err.code, Code::EOpen);
+}
@@ -0,0 +1,46 @@
+#![cfg(all(feature = "encoding", unix, not(target_os = "macos")))]
+
+use unrar::encoding_rs::{IBM866, SHIFT_JIS};
+use unrar::Archive;
+
+fn names(archive: Archive) -> Vec<String> {
+    archive
+        .open_for_listing()
+        .unwrap()
+        .map(|entry| entry.unwrap().filename.to_string_lossy().into_owned())
+        .collect()
+}
+
+#[test]
+fn cp866() {
+    let archive = Archive::new("data/cp866.rar").with_filename_encoding(IBM866);
+    assert_eq!(names(archive), ["Привет.txt", "readme.txt"]);
+}
+
+#[test]
+fn shift_jis() {
+    let archive = Archive::new("data/shift-jis.rar").with_filename_encoding(SHIFT_JIS);
+    assert_eq!(names(archive), ["データ.txt"]);
+}
+
+#[test]
+fn keeps_unicode_names() {
+    let archive = Archive::new("data/unicode-dirs.rar").with_filename_encoding(IBM866);
+    assert_eq!(names(archive)[1], "dïr❤️/fïle✌.txt");
+}
+
+#[test]
+fn extract_decoded() {
+    let dest = tempfile::tempdir().unwrap();
+    let mut archive = Archive::new("data/cp866.rar")
+        .with_filename_encoding(IBM866)
+        .open_for_processing()
+        .unwrap();
+    while let Some(header) = archive.read_header().unwrap() {
+        let path = dest.path().join(&header.entry().filename);
+        archive = header.extract_to(&path).unwrap();
+    }
+    let data = std::fs::read_to_string(dest.path().join("Привет.txt")).unwrap();
+    assert_eq!(data, "privet\n");
+    assert!(dest.path().join("readme.txt").is_file());
+}
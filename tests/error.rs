@@ -0,0 +1,87 @@
+use std::error::Error;
+use std::path::Path;
+use unrar::error::{Code, When};
+use unrar::Archive;
+
+#[test]
+fn unknown_code() {
+    assert_eq!(Code::from(24), Code::BadPassword);
+    assert_eq!(Code::from(1234), Code::Other(1234));
+}
+
+#[test]
+fn missing_volume_context() {
+    let mut archive = Archive::new("data/archive.part1.rar")
+        .open_for_listing()
+        .unwrap();
+    let err = archive.find_map(Result::err).unwrap();
+    assert_eq!((err.code, err.when), (Code::EOpen, When::Process));
+    assert_eq!(err.filename(), Some(Path::new("vendor/unrar/archive.cpp")));
+    assert_eq!(err.volume(), Some(Path::new("data/archive.part2.rar")));
+}
+
+#[test]
+fn open_context() {
+    let err = Archive::new("data/does-not-exist.rar")
+        .open_for_listing()
+        .unwrap_err();
+    assert_eq!((err.code, err.when), (Code::EOpen, When::Open));
+    assert_eq!(err.volume(), Some(Path::new("data/does-not-exist.rar")));
+    assert_eq!(err.filename(), None);
+    let source = err.io_error().unwrap();
+    assert_eq!(source.kind(), std::io::ErrorKind::NotFound);
+    assert!(err.source().is_some());
+}
+
+#[test]
+fn create_context() {
+    let parent = tempfile::tempdir().unwrap();
+    // a regular file where a directory is expected cannot be created into
+    let blocker = parent.path().join("blocker");
+    std::fs::write(&blocker, "").unwrap();
+    let err = Archive::new("data/version.rar")
+        .open_for_processing()
+        .unwrap()
+        .read_header()
+        .unwrap()
+        .unwrap()
+        .extract_to(blocker.join("VERSION"))
+        .unwrap_err();
+    assert_eq!((err.code, err.when), (Code::ECreate, When::Process));
+    assert_eq!(err.filename(), Some(Path::new("VERSION")));
+    assert_eq!(err.volume(), Some(Path::new("data/version.rar")));
+    assert!(err.io_error().is_some());
+}
+
+#[test]
+fn nul_in_filename() {
+    let err = Archive::new("data/ver\0sion.rar")
+        .open_for_listing()
+        .unwrap_err();
+    assert_eq!((err.code, err.when), (Code::Nul, When::Open));
+    assert_eq!(err.nul_error().unwrap().nul_position(), 8);
+}
+
+#[test]
+fn nul_in_password() {
+    let err = Archive::with_password("data/crypted.rar", b"un\0rar")
+        .open_for_processing()
+        .unwrap_err();
+    assert_eq!((err.code, err.when), (Code::Nul, When::Open));
+    assert_eq!(err.nul_error().unwrap().nul_position(), 2);
+}
+
+#[test]
+fn nul_in_destination() {
+    let err = Archive::new("data/version.rar")
+        .open_for_processing()
+        .unwrap()
+        .read_header()
+        .unwrap()
+        .unwrap()
+        .extract_to("VER\0SION")
+        .unwrap_err();
+    assert_eq!((err.code, err.when), (Code::Nul, When::Process));
+    assert_eq!(err.filename(), Some(Path::new("VERSION")));
+    assert!(err.source().is_some());
+}
@@ -0,0 +1,146 @@
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+use unrar::{detect, Archive, Locator, RarFormat, VolumeInfo};
+
+#[test]
+fn rar4() {
+    let format = detect("data/version.rar").unwrap();
+    assert_eq!(format.version, RarFormat::Rar15to4);
+    assert_eq!(format.volume, Some(VolumeInfo::None));
+    assert!(!format.is_sfx());
+}
+
+#[test]
+fn rar5() {
+    let format = detect(Path::new("data/multi.rar")).unwrap();
+    assert_eq!(format.version, RarFormat::Rar5);
+    assert_eq!(format.volume, Some(VolumeInfo::None));
+}
+
+#[test]
+fn rar7() {
+    let format = detect("data/rar7.rar").unwrap();
+    assert_eq!(format.version, RarFormat::Rar7);
+    let archive = Archive::new("data/rar7.rar").open_for_listing().unwrap();
+    assert_eq!(archive.format(), Some(RarFormat::Rar7));
+}
+
+#[test]
+fn rar14() {
+    // signature, header size, volume flag and the first file split from a previous volume
+    let mut volume = b"RE~^\x07\x00\x01".to_vec();
+    volume.extend_from_slice(&[0; 17]);
+    volume.push(0x01);
+    let format = detect(&volume).unwrap();
+    assert_eq!(format.version, RarFormat::Rar14);
+    assert_eq!(format.volume, Some(VolumeInfo::Subsequent));
+
+    // unrar only finds a RAR 1.4 archive after an SFX module marked as such
+    let mut sfx = b"MZ".to_vec();
+    sfx.resize(0x100, 0);
+    sfx.extend_from_slice(b"RE~^\x07\x00\x00");
+    assert_eq!(detect(&sfx), None);
+    sfx[28..32].copy_from_slice(b"RSFX");
+    let format = detect(&sfx).unwrap();
+    assert_eq!(format.version, RarFormat::Rar14);
+    assert_eq!(format.offset, 0x100);
+}
+
+#[test]
+fn metadata() {
+    let archive = Archive::new("data/metadata.rar")
+        .open_for_listing()
+        .unwrap();
+    assert_eq!(archive.format(), Some(RarFormat::Rar5));
+    assert_eq!(archive.original_name(), Some("original.rar"));
+    let created = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+    assert_eq!(archive.created(), Some(created));
+    assert!(!archive.has_quick_open());
+
+    let archive = Archive::new("data/version.rar").open_for_listing().unwrap();
+    assert_eq!(archive.original_name(), None);
+    assert_eq!(archive.created(), None);
+    assert_eq!(archive.locator(), None);
+}
+
+#[test]
+fn locator() {
+    let archive = Archive::new("data/recovery-record.rar")
+        .open_for_listing()
+        .unwrap();
+    let locator = Locator {
+        quick_open: None,
+        recovery: Some(87),
+    };
+    assert_eq!(archive.locator(), Some(locator));
+    assert!(!archive.has_quick_open());
+
+    // the record it points to is not there, which unrar shrugs off
+    let archive = Archive::new("data/locator.rar").open_for_listing().unwrap();
+    let locator = Locator {
+        quick_open: Some(72),
+        recovery: None,
+    };
+    assert_eq!(archive.locator(), Some(locator));
+    assert!(archive.has_quick_open());
+    assert_eq!(archive.count(), 1);
+}
+
+#[test]
+fn volumes() {
+    let first = detect("data/archive.part1.rar").unwrap();
+    assert_eq!(first.volume, Some(VolumeInfo::First));
+    let first = detect("data/volumes.part1.rar").unwrap();
+    assert_eq!(
+        (first.version, first.volume),
+        (RarFormat::Rar5, Some(VolumeInfo::First))
+    );
+    let second = detect("data/volumes.part2.rar").unwrap();
+    assert_eq!(second.volume, Some(VolumeInfo::Subsequent));
+    let later = detect("data/100M.part00002.rar").unwrap();
+    assert_eq!(later.volume, Some(VolumeInfo::Subsequent));
+}
+
+#[test]
+fn encrypted_headers() {
+    let format = detect("data/comment-hpw-password.rar").unwrap();
+    assert_eq!(format.version, RarFormat::Rar5);
+    assert_eq!(format.volume, None);
+}
+
+#[test]
+fn sfx() {
+    // an executable stub that happens to contain a signature without a valid archive after it
+    let mut sfx = b"MZ\x90\x00".to_vec();
+    sfx.resize(0x400, 0);
+    sfx.extend_from_slice(b"Rar!\x1a\x07\x00garbage");
+    sfx.resize(0x1000, 0);
+    sfx.extend_from_slice(&std::fs::read("data/multi.rar").unwrap());
+    let format = detect(&sfx).unwrap();
+    assert_eq!(format.version, RarFormat::Rar5);
+    assert_eq!(format.offset, 0x1000);
+    assert!(format.is_sfx());
+}
+
+#[test]
+fn not_an_archive() {
+    assert_eq!(detect("Cargo.toml"), None);
+    assert_eq!(detect("data/does-not-exist.rar"), None);
+    assert_eq!(detect(b"Rar!\x1a\x07\x02\x00"), None);
+    assert_eq!(detect(&b""[..]), None);
+}
+
+#[test]
+#[cfg(unix)]
+fn main_header_read_lazily() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("version.rar");
+    std::fs::copy("data/version.rar", &path).unwrap();
+    let archive = Archive::new(&path).open_for_processing().unwrap();
+    // the open handle keeps the file readable for unrar, but it cannot be read by name again
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(archive.format(), None);
+    assert_eq!(archive.locator(), None);
+    let entry = archive.read_header().unwrap().unwrap();
+    assert_eq!(entry.read().unwrap().0, b"unrar-0.4.0");
+}
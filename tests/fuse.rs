@@ -0,0 +1,92 @@
+#![cfg(all(feature = "fuse", target_os = "linux"))]
+
+use std::fs;
+use std::io::ErrorKind;
+use std::os::unix::fs::FileExt;
+use std::path::Path;
+use tempfile::TempDir;
+use unrar::fuse::{Mount, RarFs};
+use unrar::Archive;
+
+/// mounts `fs` at a new directory, which is unmounted before it is removed
+fn mount(fs: RarFs) -> (Mount, TempDir) {
+    let dir = tempfile::tempdir().unwrap();
+    (fs.spawn_mount(dir.path()).unwrap(), dir)
+}
+
+#[test]
+fn browse() {
+    let (_mount, dir) = mount(RarFs::new(Archive::new("data/multi.rar")).unwrap());
+    let mut names: Vec<_> = fs::read_dir(dir.path().join("src"))
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name())
+        .collect();
+    names.sort();
+    assert_eq!(names, ["lib.rs", "util.rs"]);
+    assert_eq!(
+        fs::read(dir.path().join("VERSION")).unwrap(),
+        b"unrar-0.5.8"
+    );
+    let metadata = fs::metadata(dir.path().join("VERSION")).unwrap();
+    assert_eq!(metadata.len(), 11);
+    // mounted read-only
+    assert!(fs::write(dir.path().join("VERSION"), b"").is_err());
+    let missing = fs::metadata(dir.path().join("missing")).unwrap_err();
+    assert_eq!(missing.kind(), ErrorKind::NotFound);
+}
+
+#[test]
+fn links() {
+    let (_mount, dir) = mount(RarFs::new(Archive::new("data/links.rar")).unwrap());
+    let link = dir.path().join("dir/link");
+    assert_eq!(fs::read_link(&link).unwrap(), Path::new("file.txt"));
+    assert_eq!(fs::read(&link).unwrap(), b"linked file\n");
+    assert_eq!(
+        fs::read(dir.path().join("hard.txt")).unwrap(),
+        b"linked file\n"
+    );
+}
+
+#[test]
+fn multipart() {
+    let archive = Archive::new("data/volumes.part2.rar").as_first_part();
+    let (_mount, dir) = mount(RarFs::new(archive).unwrap().with_cache_size(0));
+    let split = fs::read_to_string(dir.path().join("split.txt")).unwrap();
+    assert_eq!(split, "split across two volumes\n");
+    assert_eq!(fs::read(dir.path().join("last.txt")).unwrap().len(), 5);
+}
+
+#[test]
+fn password() {
+    let archive = Archive::with_password("data/crypted.rar", "unrar");
+    let (_mount, dir) = mount(RarFs::new(archive).unwrap());
+    let data = fs::read_to_string(dir.path().join(".gitignore")).unwrap();
+    assert_eq!(data, "target\nCargo.lock\n");
+
+    let (_mount, dir) = mount(RarFs::new(Archive::new("data/crypted.rar")).unwrap());
+    let err = fs::read(dir.path().join(".gitignore")).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::PermissionDenied);
+}
+
+#[test]
+fn larger_than_cache() {
+    // compressed, so decompressed as it is read
+    let fs = RarFs::new(Archive::new("data/version.rar")).unwrap();
+    let (_mount, dir) = mount(fs.with_cache_size(4));
+    let file = fs::File::open(dir.path().join("VERSION")).unwrap();
+    let mut buf = [0; 5];
+    assert_eq!(file.read_at(&mut buf, 6).unwrap(), 5);
+    assert_eq!(&buf, b"0.4.0");
+    // reading backwards starts over
+    assert_eq!(file.read_at(&mut buf, 0).unwrap(), 5);
+    assert_eq!(&buf, b"unrar");
+    assert_eq!(
+        fs::read(dir.path().join("VERSION")).unwrap(),
+        b"unrar-0.4.0"
+    );
+
+    let fs = RarFs::new(Archive::new("data/crypted.rar")).unwrap();
+    let (_mount, dir) = mount(fs.with_cache_size(0));
+    let err = fs::read(dir.path().join(".gitignore")).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::PermissionDenied);
+}
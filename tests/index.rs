@@ -0,0 +1,86 @@
+use std::path::PathBuf;
+use unrar::Archive;
+
+#[test]
+fn index_entries() {
+    let index = Archive::new("data/multi.rar").index().unwrap();
+    let names = index
+        .entries()
+        .iter()
+        .map(|e| e.filename.clone())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        names,
+        ["README.md", "src", "src/lib.rs", "src/util.rs", "VERSION"].map(PathBuf::from)
+    );
+    assert!(!index.is_solid());
+    assert_eq!(index.offset("README.md"), Some(0));
+    assert_eq!(index.offset("src/util.rs"), Some(3));
+    assert_eq!(index.offset("missing"), None);
+    assert_eq!(index.get("src/lib.rs").unwrap().unpacked_size, 13);
+}
+
+#[test]
+fn open_entry() {
+    let index = Archive::new("data/multi.rar").index().unwrap();
+    let entry = Archive::new("data/multi.rar")
+        .open_entry(&index, "src/util.rs")
+        .unwrap()
+        .unwrap();
+    assert_eq!(entry.entry().filename, PathBuf::from("src/util.rs"));
+    let (data, rest) = entry.read().unwrap();
+    assert_eq!(data, b"pub fn util() {}\n");
+    // processing continues after the entry
+    let next = rest.read_header().unwrap().unwrap();
+    assert_eq!(next.entry().filename, PathBuf::from("VERSION"));
+}
+
+#[test]
+fn open_missing_entry() {
+    let index = Archive::new("data/multi.rar").index().unwrap();
+    let entry = Archive::new("data/multi.rar")
+        .open_entry(&index, "missing")
+        .unwrap();
+    assert!(entry.is_none());
+}
+
+#[test]
+fn open_entry_solid() {
+    let index = Archive::new("data/solid.rar").index().unwrap();
+    assert!(index.is_solid());
+    let entry = Archive::new("data/solid.rar")
+        .open_entry(&index, ".gitignore")
+        .unwrap()
+        .unwrap();
+    assert_eq!(entry.read().unwrap().0, b"target\nCargo.lock\n");
+}
+
+#[test]
+fn open_entry_foreign_index() {
+    let index = Archive::new("data/multi.rar").index().unwrap();
+    // unicode-dirs.rar has an entry at the offset of src/util.rs, but not the same one
+    let err = Archive::new("data/unicode-dirs.rar")
+        .open_entry(&index, "src/util.rs")
+        .unwrap_err();
+    assert_eq!(err.code, unrar::error::Code::BadArchive);
+    // and none at all at the offset of VERSION
+    let err = Archive::new("data/unicode-dirs.rar")
+        .open_entry(&index, "VERSION")
+        .unwrap_err();
+    assert_eq!(err.code, unrar::error::Code::BadArchive);
+}
+
+#[test]
+fn open_entry_solid_foreign_index() {
+    let index = Archive::new("data/solid-stored.rar").index().unwrap();
+    // solid-changed.rar has a second.txt too, but with other content
+    let err = Archive::new("data/solid-changed.rar")
+        .open_entry(&index, "second.txt")
+        .unwrap_err();
+    assert_eq!(err.code, unrar::error::Code::BadArchive);
+    // and solid.rar has no first.txt at all
+    let err = Archive::new("data/solid.rar")
+        .open_entry(&index, "first.txt")
+        .unwrap_err();
+    assert_eq!(err.code, unrar::error::Code::BadArchive);
+}
@@ -0,0 +1,119 @@
+use std::path::Path;
+use unrar::error::{Code, When};
+use unrar::{Archive, ExtractLimits, Limit, UnrarResult};
+
+fn read_all(archive: Archive) -> UnrarResult<Vec<Vec<u8>>> {
+    let mut archive = archive.open_for_processing()?;
+    let mut contents = Vec::new();
+    while let Some(header) = archive.read_header()? {
+        let (data, rest) = header.read()?;
+        contents.push(data);
+        archive = rest;
+    }
+    Ok(contents)
+}
+
+fn list_error(limits: ExtractLimits) -> unrar::error::UnrarError {
+    let archive = Archive::new("data/multi.rar").with_limits(limits);
+    archive
+        .open_for_listing()
+        .unwrap()
+        .find_map(Result::err)
+        .unwrap()
+}
+
+#[test]
+fn declared_entry_size() {
+    let err = list_error(ExtractLimits::new().max_entry_size(16));
+    assert_eq!((err.code, err.when), (Code::LimitExceeded, When::Read));
+    assert_eq!(err.limit(), Some(Limit::EntrySize));
+    assert_eq!(err.filename(), Some(Path::new("src/util.rs")));
+}
+
+#[test]
+fn entries() {
+    let err = list_error(ExtractLimits::new().max_entries(2));
+    assert_eq!(err.limit(), Some(Limit::Entries));
+    assert_eq!(err.filename(), Some(Path::new("src/lib.rs")));
+}
+
+#[test]
+fn path_depth() {
+    let err = list_error(ExtractLimits::new().max_path_depth(1));
+    assert_eq!(err.limit(), Some(Limit::PathDepth));
+    assert_eq!(err.filename(), Some(Path::new("src/lib.rs")));
+}
+
+#[test]
+fn total_size() {
+    // README.md and src/lib.rs are 21 bytes together
+    let limits = ExtractLimits::new().max_total_size(20);
+    let err = read_all(Archive::new("data/multi.rar").with_limits(limits)).unwrap_err();
+    assert_eq!((err.code, err.when), (Code::LimitExceeded, When::Process));
+    assert_eq!(err.limit(), Some(Limit::TotalSize));
+    assert_eq!(err.filename(), Some(Path::new("src/lib.rs")));
+    assert_eq!(err.to_string(), "Exceeded the total size limit");
+}
+
+#[test]
+fn ratio() {
+    // build.rs is compressed from 2396 to 546 bytes
+    let limits = ExtractLimits::new().max_ratio(4);
+    let err = read_all(Archive::new("data/archive.part1.rar").with_limits(limits)).unwrap_err();
+    assert_eq!(err.limit(), Some(Limit::Ratio));
+    assert_eq!(err.filename(), Some(Path::new("build.rs")));
+}
+
+#[test]
+fn path_depth_parent() {
+    // `..` leads out of the archive, which takes a level just like a directory
+    let limits = ExtractLimits::new().max_path_depth(1);
+    let err = Archive::new("data/traversal.rar")
+        .with_limits(limits)
+        .open_for_listing()
+        .unwrap()
+        .find_map(Result::err)
+        .unwrap();
+    assert_eq!(err.limit(), Some(Limit::PathDepth));
+    assert_eq!(err.filename(), Some(Path::new("../evil.txt")));
+}
+
+#[test]
+fn ratio_split() {
+    // split.txt is stored, 10 of its 25 bytes in the first volume and 15 in the second
+    let limits = ExtractLimits::new().max_ratio(1);
+    let contents = read_all(Archive::new("data/volumes.part1.rar").with_limits(limits)).unwrap();
+    assert_eq!(contents[1], b"split across two volumes\n");
+
+    let limits = ExtractLimits::new().max_ratio(0);
+    let archive = Archive::new("data/volumes.part1.rar").with_limits(limits);
+    let archive = archive.open_for_processing().unwrap();
+    let archive = archive.read_header().unwrap().unwrap().skip().unwrap();
+    let header = archive.read_header().unwrap().unwrap();
+    let err = header.read().unwrap_err();
+    assert_eq!(err.limit(), Some(Limit::Ratio));
+    assert_eq!(err.filename(), Some(Path::new("split.txt")));
+}
+
+#[test]
+fn within_limits() {
+    let limits = ExtractLimits::new()
+        .max_total_size(57)
+        .max_entry_size(17)
+        .max_entries(5)
+        .max_ratio(1)
+        .max_path_depth(2);
+    let contents = read_all(Archive::new("data/multi.rar").with_limits(limits)).unwrap();
+    assert_eq!(contents.len(), 5);
+}
+
+#[test]
+fn total_size_parallel() {
+    let dest = tempfile::tempdir().unwrap();
+    let limits = ExtractLimits::new().max_total_size(20);
+    let err = Archive::new("data/multi.rar")
+        .with_limits(limits)
+        .extract_all_parallel(dest.path(), 4)
+        .unwrap_err();
+    assert_eq!(err.limit(), Some(Limit::TotalSize));
+}
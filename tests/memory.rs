@@ -0,0 +1,57 @@
+use std::path::Path;
+use unrar::{Archive, Link, LinkKind, MemoryNode};
+
+#[test]
+fn extract_all_to_memory() {
+    let tree = Archive::new("data/links.rar")
+        .extract_all_to_memory()
+        .unwrap();
+    let paths: Vec<_> = tree.iter().map(|(path, _)| path).collect();
+    assert_eq!(
+        paths,
+        ["dir", "dir/file.txt", "dir/link", "hard.txt"].map(Path::new)
+    );
+    assert_eq!(tree.get("dir").unwrap().node, MemoryNode::Directory);
+    assert_eq!(tree.read("dir/file.txt"), Some(&b"linked file\n"[..]));
+    let symlink = Link {
+        kind: LinkKind::UnixSymlink,
+        target: "file.txt".into(),
+        directory: false,
+    };
+    assert_eq!(
+        tree.get("dir/link").unwrap().node,
+        MemoryNode::Link(symlink)
+    );
+    assert_eq!(tree.read("dir/link"), None);
+    let MemoryNode::Link(hardlink) = &tree.get("hard.txt").unwrap().node else {
+        panic!("not a link");
+    };
+    assert_eq!(hardlink.kind, LinkKind::Hardlink);
+    let top: Vec<_> = tree.children("").map(|(path, _)| path).collect();
+    assert_eq!(top, ["dir", "hard.txt"].map(Path::new));
+    assert_eq!(tree.children("dir").count(), 2);
+}
+
+#[test]
+fn metadata() {
+    let tree = Archive::new("data/version.rar")
+        .extract_all_to_memory()
+        .unwrap();
+    let entry = tree.get("VERSION").unwrap();
+    assert_eq!(entry.header.unpacked_size, 11);
+    assert_eq!(entry.node, MemoryNode::File(b"unrar-0.4.0".to_vec()));
+}
+
+#[test]
+fn normalized_paths() {
+    // dir/./one.txt and dir//one.txt are the same file, the later one wins
+    let tree = Archive::new("data/dotted.rar")
+        .extract_all_to_memory()
+        .unwrap();
+    let paths: Vec<_> = tree.iter().map(|(path, _)| path).collect();
+    assert_eq!(paths, ["dir", "dir/one.txt", "dir/two.txt"].map(Path::new));
+    assert_eq!(tree.read("dir/one.txt"), Some(&b"second\n"[..]));
+    assert_eq!(tree.read("./dir//two.txt"), Some(&b"two\n"[..]));
+    assert_eq!(tree.children("dir/.").count(), 2);
+    assert!(tree.get("../dir").is_none());
+}
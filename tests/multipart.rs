@@ -20,3 +20,33 @@ fn list_missing_volume() {
     let data = archive.next().unwrap().unwrap_err();
     assert_eq!(format!("{data}"), "Could not open next volume");
 }
+
+#[test]
+fn list_split_across_volumes() {
+    let names = Archive::new("data/volumes.part1.rar")
+        .open_for_listing_split()
+        .unwrap()
+        .map(|entry| entry.unwrap().filename)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        names,
+        ["first.txt", "split.txt", "split.txt", "last.txt"].map(PathBuf::from)
+    );
+}
+
+#[test]
+fn read_split_across_volumes() {
+    let mut archive = Archive::new("data/volumes.part1.rar")
+        .open_for_processing()
+        .unwrap();
+    let mut contents = Vec::new();
+    while let Some(header) = archive.read_header().unwrap() {
+        let (data, rest) = header.read().unwrap();
+        contents.push(data);
+        archive = rest;
+    }
+    assert_eq!(
+        contents,
+        [&b"first\n"[..], b"split across two volumes\n", b"last\n"]
+    );
+}
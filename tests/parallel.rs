@@ -0,0 +1,56 @@
+use unrar::Archive;
+
+#[test]
+fn extract_parallel() {
+    let dest = tempfile::tempdir().unwrap();
+    Archive::new("data/multi.rar")
+        .extract_all_parallel(dest.path(), 3)
+        .unwrap();
+    let read = |name| std::fs::read_to_string(dest.path().join(name)).unwrap();
+    assert_eq!(read("README.md"), "# multi\n");
+    assert_eq!(read("src/lib.rs"), "fn main() {}\n");
+    assert_eq!(read("src/util.rs"), "pub fn util() {}\n");
+    assert_eq!(read("VERSION"), "unrar-0.5.8");
+}
+
+#[test]
+fn extract_parallel_more_threads_than_entries() {
+    let dest = tempfile::tempdir().unwrap();
+    Archive::new("data/version.rar")
+        .extract_all_parallel(dest.path(), 8)
+        .unwrap();
+    let version = std::fs::read_to_string(dest.path().join("VERSION")).unwrap();
+    assert_eq!(version, "unrar-0.4.0");
+}
+
+#[test]
+fn extract_parallel_solid() {
+    let dest = tempfile::tempdir().unwrap();
+    Archive::new("data/solid.rar")
+        .extract_all_parallel(dest.path(), 4)
+        .unwrap();
+    let gitignore = std::fs::read_to_string(dest.path().join(".gitignore")).unwrap();
+    assert_eq!(gitignore, "target\nCargo.lock\n");
+}
+
+#[test]
+fn extract_parallel_missing_volume() {
+    let dest = tempfile::tempdir().unwrap();
+    let err = Archive::new("data/archive.part1.rar")
+        .extract_all_parallel(dest.path(), 2)
+        .unwrap_err();
+    assert_eq!(err.to_string(), "Could not open next volume");
+    assert!(dest.path().join("build.rs").exists());
+}
+
+#[test]
+fn extract_parallel_nested_directories() {
+    let dest = tempfile::tempdir().unwrap();
+    Archive::new("data/unicode-dirs.rar")
+        .extract_all_parallel(dest.path(), 4)
+        .unwrap();
+    let dir = dest.path().join("dïr❤️");
+    assert!(dir.join("sub").is_dir());
+    assert_eq!(std::fs::read(dir.join("fïle✌.txt")).unwrap().len(), 8);
+    assert_eq!(std::fs::read(dir.join("sub/plain.txt")).unwrap().len(), 6);
+}
@@ -0,0 +1,68 @@
+use unrar::error::{Code, When};
+use unrar::Archive;
+
+#[test]
+fn goes_on_past_damaged() {
+    let report = Archive::new("data/bad-crc.rar").test_all().unwrap();
+    assert!(!report.is_ok());
+    assert!(report.error.is_none());
+    let names: Vec<_> = report
+        .entries
+        .iter()
+        .map(|(entry, _)| entry.filename.to_str().unwrap())
+        .collect();
+    assert_eq!(names, ["good.txt", "bad.txt"]);
+    assert!(report.entries[0].1.is_ok());
+    let (entry, error) = report.failed().next().unwrap();
+    assert_eq!(entry.filename.to_str(), Some("bad.txt"));
+    assert_eq!((error.code, error.when), (Code::BadData, When::Process));
+}
+
+#[test]
+fn all_ok() {
+    let report = Archive::new("data/volumes.part1.rar").test_all().unwrap();
+    assert!(report.is_ok());
+    // the continuation of split.txt is left out
+    assert_eq!(report.entries.len(), 3);
+}
+
+#[test]
+fn keep_broken() {
+    let dest = tempfile::tempdir().unwrap();
+    let report = Archive::new("data/bad-crc.rar")
+        .extract_all_keep_broken(dest.path())
+        .unwrap();
+    assert_eq!(report.failed().count(), 1);
+    assert!(dest.path().join("good.txt").is_file());
+    assert!(dest.path().join("bad.txt").is_file());
+
+    // unrar deletes damaged files otherwise
+    let dest = tempfile::tempdir().unwrap();
+    let mut archive = Archive::new("data/bad-crc.rar")
+        .open_for_processing()
+        .unwrap();
+    while let Some(header) = archive.read_header().unwrap() {
+        match header.extract_with_base(dest.path()) {
+            Ok(rest) => archive = rest,
+            Err(_) => break,
+        }
+    }
+    assert!(!dest.path().join("bad.txt").exists());
+}
+
+#[test]
+fn stops_early() {
+    let report = Archive::new("data/archive.part1.rar").test_all().unwrap();
+    assert!(!report.is_ok());
+    assert!(report.failed().next().is_none());
+    assert!(!report.entries.is_empty());
+    let error = report.error.unwrap();
+    assert_eq!((error.code, error.when), (Code::EOpen, When::Process));
+
+    // unrar cannot go on without a password either
+    let report = Archive::new("data/crypted.rar").test_all().unwrap();
+    assert!(report.entries.is_empty());
+    let error = report.error.unwrap();
+    assert_eq!(error.code, Code::MissingPassword);
+    assert_eq!(error.filename().unwrap().to_str(), Some(".gitignore"));
+}
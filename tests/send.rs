@@ -0,0 +1,69 @@
+use std::path::PathBuf;
+use std::thread;
+use unrar::{Archive, CursorBeforeFile, CursorBeforeHeader, List, OpenArchive, Process};
+
+fn assert_send<T: Send>() {}
+
+#[test]
+fn open_archive_is_send() {
+    assert_send::<OpenArchive<List, CursorBeforeHeader>>();
+    assert_send::<OpenArchive<List, CursorBeforeFile>>();
+    assert_send::<OpenArchive<Process, CursorBeforeHeader>>();
+    assert_send::<OpenArchive<Process, CursorBeforeFile>>();
+}
+
+#[test]
+fn move_listing_mid_iteration() {
+    let mut archive = Archive::new("data/multi.rar").open_for_listing().unwrap();
+    let first = archive.next().unwrap().unwrap();
+    assert_eq!(first.filename, PathBuf::from("README.md"));
+    let rest = thread::spawn(move || {
+        archive
+            .map(|entry| entry.unwrap().filename)
+            .collect::<Vec<_>>()
+    })
+    .join()
+    .unwrap();
+    assert_eq!(
+        rest,
+        ["src", "src/lib.rs", "src/util.rs", "VERSION"].map(PathBuf::from)
+    );
+}
+
+#[test]
+fn move_processing_between_threads() {
+    let archive = Archive::new("data/multi.rar")
+        .open_for_processing()
+        .unwrap();
+    let archive = archive.read_header().unwrap().unwrap();
+    // read the first entry on another thread, then hand the archive back
+    let (data, archive) = thread::spawn(move || archive.read().unwrap())
+        .join()
+        .unwrap();
+    assert_eq!(data, b"# multi\n");
+    let archive = archive.read_header().unwrap().unwrap().skip().unwrap();
+    let header = archive.read_header().unwrap().unwrap();
+    // move an archive whose cursor is before a file
+    let (name, data) = thread::spawn(move || {
+        let name = header.entry().filename.clone();
+        (name, header.read().unwrap().0)
+    })
+    .join()
+    .unwrap();
+    assert_eq!(name, PathBuf::from("src/lib.rs"));
+    assert_eq!(data, b"fn main() {}\n");
+}
+
+#[test]
+fn move_encrypted_between_threads() {
+    let archive = Archive::with_password("data/comment-hpw-password.rar", "password")
+        .open_for_processing()
+        .unwrap();
+    let data = thread::spawn(move || {
+        let archive = archive.read_header().unwrap().unwrap();
+        archive.read().unwrap().0
+    })
+    .join()
+    .unwrap();
+    assert_eq!(data, b"target\nCargo.lock\n");
+}
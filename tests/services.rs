@@ -0,0 +1,160 @@
+use std::path::{Path, PathBuf};
+use unrar::{Archive, EntryKind, ListOptions};
+
+const SERVICES: ListOptions = ListOptions {
+    include_services: true,
+};
+
+fn stream(parent: &str, name: &str) -> EntryKind {
+    EntryKind::Stream {
+        parent: PathBuf::from(parent),
+        name: name.to_owned(),
+    }
+}
+
+#[test]
+fn list() {
+    let entries = Archive::new("data/streams.rar").list(SERVICES).unwrap();
+    let names: Vec<_> = entries
+        .iter()
+        .map(|e| e.filename.to_str().unwrap())
+        .collect();
+    assert_eq!(
+        names,
+        [
+            "CMT",
+            "streams.txt",
+            "streams.txt:Zone.Identifier",
+            "streams.txt:packed",
+            "after.txt",
+        ]
+    );
+    let service = EntryKind::Service {
+        name: "CMT".to_owned(),
+    };
+    assert_eq!(entries[0].kind(), &service);
+    assert_eq!(entries[1].kind(), &EntryKind::File);
+    assert_eq!(entries[2].kind(), &stream("streams.txt", "Zone.Identifier"));
+    // written like RAR 5.21 did, with the size of the stream name off by one
+    assert_eq!(entries[3].kind(), &stream("streams.txt", "packed"));
+    assert_eq!(entries[3].unpacked_size, 40);
+
+    let entries = Archive::new("data/streams4.rar").list(SERVICES).unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[1].kind(), &stream("streams.txt", "legacy"));
+    assert_eq!(entries[1].packed_size, 11);
+}
+
+#[test]
+fn without_services() {
+    let entries = Archive::new("data/streams.rar")
+        .list(ListOptions::default())
+        .unwrap();
+    assert_eq!(entries.len(), 2);
+    assert!(entries.iter().all(|e| e.kind() == &EntryKind::File));
+    // archives with encrypted headers only have the entries unrar lists
+    let entries = Archive::with_password("data/comment-hpw-password.rar", "password")
+        .list(SERVICES)
+        .unwrap();
+    assert!(entries.iter().all(|e| e.kind() == &EntryKind::File));
+}
+
+#[test]
+fn time_out_of_range() {
+    // the modification time is a FILETIME just above i64::MIN, which must not overflow
+    // converting it to a Unix time
+    let entries = Archive::new("data/htime-overflow.rar")
+        .list(SERVICES)
+        .unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].filename.to_str(), Some("early.txt"));
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use super::*;
+    use unrar::StreamTarget;
+
+    fn extract(dest: &Path) {
+        let mut archive = Archive::new("data/streams.rar")
+            .open_for_processing()
+            .unwrap();
+        while let Some(header) = archive.read_header().unwrap() {
+            archive = header.extract_with_base(dest).unwrap();
+        }
+    }
+
+    #[test]
+    fn sidecar() {
+        let dest = tempfile::tempdir().unwrap();
+        extract(dest.path());
+        let skipped = Archive::new("data/streams.rar")
+            .extract_streams(dest.path(), StreamTarget::Sidecar)
+            .unwrap();
+        assert_eq!(skipped.len(), 1);
+        assert_eq!(skipped[0].filename.to_str(), Some("streams.txt:packed"));
+        let zone = std::fs::read(dest.path().join("streams.txt:Zone.Identifier")).unwrap();
+        assert_eq!(zone, b"[ZoneTransfer]\r\nZoneId=3\r\n");
+    }
+
+    #[test]
+    fn traversal() {
+        let dest = tempfile::tempdir().unwrap();
+        let base = dest.path().join("base");
+        std::fs::create_dir(&base).unwrap();
+        std::fs::write(dest.path().join("evil.txt"), b"outside\n").unwrap();
+        for target in [StreamTarget::Xattr, StreamTarget::Sidecar] {
+            let err = Archive::new("data/stream-traversal.rar")
+                .extract_streams(&base, target)
+                .unwrap_err();
+            assert_eq!(err.filename().unwrap().to_str(), Some("../evil.txt:zone"));
+            let kind = err.io_error().unwrap().kind();
+            assert_eq!(kind, std::io::ErrorKind::InvalidInput);
+        }
+        assert!(!dest.path().join("evil.txt:zone").exists());
+
+        std::fs::write(base.join("inside.txt"), b"inside\n").unwrap();
+        let err = Archive::new("data/stream-name.rar")
+            .extract_streams(&base, StreamTarget::Sidecar)
+            .unwrap_err();
+        assert_eq!(err.code, unrar::error::Code::ECreate);
+        assert_eq!(
+            err.io_error().unwrap().kind(),
+            std::io::ErrorKind::InvalidInput
+        );
+        assert_eq!(std::fs::read_dir(&base).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn symbolic_link() {
+        let dest = tempfile::tempdir().unwrap();
+        let base = dest.path().join("base");
+        std::fs::create_dir(&base).unwrap();
+        let outside = dest.path().join("outside.txt");
+        std::fs::write(&outside, b"outside\n").unwrap();
+        // planted by an earlier extraction in place of the file the streams belong to
+        std::os::unix::fs::symlink(&outside, base.join("streams.txt")).unwrap();
+        for target in [StreamTarget::Xattr, StreamTarget::Sidecar] {
+            let err = Archive::new("data/streams.rar")
+                .extract_streams(&base, target)
+                .unwrap_err();
+            let kind = err.io_error().unwrap().kind();
+            assert_eq!(kind, std::io::ErrorKind::InvalidInput);
+        }
+        assert!(!dest.path().join("outside.txt:Zone.Identifier").exists());
+        assert!(!base.join("streams.txt:Zone.Identifier").exists());
+    }
+
+    #[test]
+    fn missing_parent() {
+        let dest = tempfile::tempdir().unwrap();
+        let err = Archive::new("data/streams.rar")
+            .extract_streams(dest.path(), StreamTarget::Xattr)
+            .unwrap_err();
+        assert_eq!(err.code, unrar::error::Code::EWrite);
+        assert_eq!(
+            err.filename().unwrap().to_str(),
+            Some("streams.txt:Zone.Identifier")
+        );
+    }
+}
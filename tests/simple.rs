@@ -37,7 +37,67 @@ fn extract_to_tempdir() {
         let temp_file_path = temp_path.join(header.entry().filename.as_path());
         archive = header.extract_to(temp_file_path.as_path()).expect("extract_to");
     }
-    let entries = std::fs::read_dir(&temp_path).expect("read tempdir").collect::<Result<Vec<_>, _>>().unwrap();
+    let entries = std::fs::read_dir(temp_path).expect("read tempdir").collect::<Result<Vec<_>, _>>().unwrap();
     assert!(entries.len() == 1);
     assert!(entries[0].file_name() == "VERSION");
 }
+
+#[test]
+fn compression() {
+    let mut archive = unrar::Archive::new("data/version.rar")
+        .open_for_listing()
+        .unwrap();
+    let entry = archive.next().unwrap().unwrap();
+    assert_eq!(entry.compression(), unrar::Method::Normal);
+    assert_eq!(entry.dictionary_size(), 128 << 10);
+
+    let entries = unrar::Archive::new("data/multi.rar")
+        .open_for_listing()
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(entries[0].compression(), unrar::Method::Store);
+    // directories need no dictionary
+    assert!(entries[1].is_directory());
+    assert_eq!(entries[1].dictionary_size(), 0);
+}
+
+#[test]
+fn solid_continuation() {
+    let archive = unrar::Archive::new("data/solid-stored.rar")
+        .open_for_listing()
+        .unwrap();
+    assert!(archive.is_solid());
+    let entries = archive.collect::<Result<Vec<_>, _>>().unwrap();
+    assert!(!entries[0].is_solid_continuation());
+    assert!(entries[1].is_solid_continuation());
+    assert_eq!(entries[1].dictionary_size(), 4 << 20);
+}
+
+#[test]
+fn raw_filename() {
+    let mut entries = unrar::Archive::new("data/version.rar").open_for_listing().unwrap();
+    assert_eq!(entries.next().unwrap().unwrap().raw_filename(), b"VERSION");
+}
+
+#[test]
+#[cfg(all(unix, not(target_os = "macos")))]
+fn raw_filename_legacy() {
+    let mut entries = unrar::Archive::new("data/cp866.rar").open_for_listing().unwrap();
+    // "Привет.txt" in CP866
+    let entry = entries.next().unwrap().unwrap();
+    assert_eq!(entry.raw_filename(), b"\x8f\xe0\xa8\xa2\xa5\xe2.txt");
+}
+
+#[test]
+#[cfg(all(unix, not(target_os = "macos")))]
+fn filename_not_utf8() {
+    use std::os::unix::ffi::OsStrExt;
+    let mut entries = unrar::Archive::new("data/cp866.rar").open_for_listing().unwrap();
+    // the bytes of the name, like extracting it would name the file
+    let entry = entries.next().unwrap().unwrap();
+    assert_eq!(
+        entry.filename.as_os_str().as_bytes(),
+        b"\x8f\xe0\xa8\xa2\xa5\xe2.txt"
+    );
+}
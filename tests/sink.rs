@@ -0,0 +1,141 @@
+use std::fs;
+use std::io;
+use unrar::error::Code;
+use unrar::{Archive, FileHeader, FsSink, Link, MemorySink, Sink};
+
+#[test]
+fn fs_sink() {
+    let dest = tempfile::tempdir().unwrap();
+    let mut sink = FsSink::new(dest.path());
+    Archive::new("data/multi.rar")
+        .extract_all(&mut sink)
+        .unwrap();
+    let lib = fs::read(dest.path().join("src/lib.rs")).unwrap();
+    let tree = Archive::new("data/multi.rar")
+        .extract_all_to_memory()
+        .unwrap();
+    assert_eq!(tree.read("src/lib.rs"), Some(&lib[..]));
+    assert!(dest.path().join("src").is_dir());
+}
+
+#[cfg(unix)]
+#[test]
+fn fs_sink_links() {
+    let dest = tempfile::tempdir().unwrap();
+    let mut sink = FsSink::new(dest.path());
+    Archive::new("data/links.rar")
+        .extract_all(&mut sink)
+        .unwrap();
+    let link = dest.path().join("dir/link");
+    assert_eq!(fs::read_link(&link).unwrap().to_str(), Some("file.txt"));
+    assert_eq!(fs::read(link).unwrap(), b"linked file\n");
+    assert_eq!(
+        fs::read(dest.path().join("hard.txt")).unwrap(),
+        b"linked file\n"
+    );
+}
+
+#[test]
+fn fs_sink_stays_below_base() {
+    let dest = tempfile::tempdir().unwrap();
+    let base = dest.path().join("base");
+    let err = Archive::new("data/traversal.rar")
+        .extract_all(&mut FsSink::new(&base))
+        .unwrap_err();
+    assert_eq!(err.code, Code::ECreate);
+    assert_eq!(err.io_error().unwrap().kind(), io::ErrorKind::InvalidInput);
+    assert!(!dest.path().join("evil.txt").exists());
+
+    let err = Archive::new("data/symlink-escape.rar")
+        .extract_all(&mut FsSink::new(&base))
+        .unwrap_err();
+    assert_eq!(err.filename().unwrap().to_str(), Some("escape"));
+    assert!(!base.join("escape").exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn fs_sink_rejects_chained_links() {
+    let dest = tempfile::tempdir().unwrap();
+    let base = dest.path().join("base");
+    let err = Archive::new("data/symlink-chain.rar")
+        .extract_all(&mut FsSink::new(&base))
+        .unwrap_err();
+    assert_eq!(err.code, Code::ECreate);
+    assert_eq!(err.filename().unwrap().to_str(), Some("d/up2"));
+    assert!(fs::symlink_metadata(base.join("d/up"))
+        .unwrap()
+        .is_symlink());
+    assert!(fs::symlink_metadata(base.join("d/up2")).is_err());
+    assert!(!dest.path().join("escaped.txt").exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn fs_sink_does_not_write_through_links() {
+    let dest = tempfile::tempdir().unwrap();
+    let base = dest.path().join("base");
+    fs::create_dir(&base).unwrap();
+    let outside = dest.path().join("outside");
+    fs::write(&outside, "untouched").unwrap();
+    std::os::unix::fs::symlink(&outside, base.join("VERSION")).unwrap();
+    let err = Archive::new("data/version.rar")
+        .extract_all(&mut FsSink::new(&base))
+        .unwrap_err();
+    assert_eq!(err.code, Code::ECreate);
+    assert_eq!(err.io_error().unwrap().kind(), io::ErrorKind::InvalidInput);
+    assert_eq!(fs::read(&outside).unwrap(), b"untouched");
+}
+
+#[test]
+fn fs_sink_discards_damaged_files() {
+    let dest = tempfile::tempdir().unwrap();
+    let err = Archive::new("data/bad-crc.rar")
+        .extract_all(&mut FsSink::new(dest.path()))
+        .unwrap_err();
+    assert_eq!(err.code, Code::BadData);
+    assert_eq!(err.filename().unwrap().to_str(), Some("bad.txt"));
+    assert!(dest.path().join("good.txt").is_file());
+    assert!(!dest.path().join("bad.txt").exists());
+}
+
+#[test]
+fn memory_sink() {
+    let mut sink = MemorySink::new();
+    Archive::new("data/version.rar")
+        .extract_all(&mut sink)
+        .unwrap();
+    let tree = sink.into_tree();
+    assert_eq!(tree.read("VERSION"), Some(&b"unrar-0.4.0"[..]));
+}
+
+/// fails after the first chunk of data
+struct Failing;
+
+impl Sink for Failing {
+    fn create_dir(&mut self, _: &FileHeader) -> io::Result<()> {
+        Ok(())
+    }
+    fn begin_file(&mut self, _: &FileHeader) -> io::Result<()> {
+        Ok(())
+    }
+    fn write_chunk(&mut self, _: &[u8]) -> io::Result<()> {
+        Err(io::Error::new(io::ErrorKind::StorageFull, "full"))
+    }
+    fn end_file(&mut self, _: &FileHeader) -> io::Result<()> {
+        panic!("the file failed")
+    }
+    fn create_link(&mut self, _: &FileHeader, _: &Link) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn sink_error() {
+    let err = Archive::new("data/version.rar")
+        .extract_all(&mut Failing)
+        .unwrap_err();
+    assert_eq!(err.code, Code::EWrite);
+    assert_eq!(err.filename().unwrap().to_str(), Some("VERSION"));
+    assert_eq!(err.io_error().unwrap().kind(), io::ErrorKind::StorageFull);
+}
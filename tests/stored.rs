@@ -0,0 +1,120 @@
+use std::io::{ErrorKind, Read, Seek, SeekFrom};
+use unrar::error::{Code, When};
+use unrar::{Archive, CursorBeforeHeader, FileHeader, List, OpenArchive};
+
+/// the entry called `name` along with the archive it was listed from
+fn find(path: &str, name: &str) -> (OpenArchive<List, CursorBeforeHeader>, FileHeader) {
+    let mut archive = Archive::new(path).open_for_listing().unwrap();
+    let entry = archive
+        .by_ref()
+        .map(Result::unwrap)
+        .find(|entry| entry.filename.to_str() == Some(name))
+        .unwrap();
+    (archive, entry)
+}
+
+#[test]
+fn across_volumes() {
+    let (archive, entry) = find("data/volumes.part1.rar", "split.txt");
+    let mut reader = archive.open_seekable(&entry).unwrap().unwrap();
+    assert_eq!(reader.size(), 25);
+    let mut data = String::new();
+    reader.read_to_string(&mut data).unwrap();
+    assert_eq!(data, "split across two volumes\n");
+
+    assert_eq!(reader.seek(SeekFrom::End(-8)).unwrap(), 17);
+    let mut end = [0; 8];
+    reader.read_exact(&mut end).unwrap();
+    assert_eq!(&end, b"volumes\n");
+    reader.seek(SeekFrom::Current(-19)).unwrap();
+    let mut across = [0; 9];
+    reader.read_exact(&mut across).unwrap();
+    assert_eq!(&across, b"across tw");
+    let err = reader.seek(SeekFrom::Current(-100)).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+}
+
+#[test]
+fn old_volume_names() {
+    let payload: Vec<u8> = (0..3000).map(|i| (i * 7 % 251) as u8).collect();
+    let (archive, entry) = find("data/stored4.rar", "movie.bin");
+    let mut reader = archive.open_seekable(&entry).unwrap().unwrap();
+    let mut data = Vec::new();
+    reader.read_to_end(&mut data).unwrap();
+    assert_eq!(data, payload);
+
+    // the first volume holds the first 1200 bytes
+    for start in [0, 1100, 1199, 1200, 2999] {
+        reader.seek(SeekFrom::Start(start)).unwrap();
+        let mut chunk = Vec::new();
+        (&mut reader).take(200).read_to_end(&mut chunk).unwrap();
+        let end = payload.len().min(start as usize + 200);
+        assert_eq!(chunk, &payload[start as usize..end]);
+    }
+    // past the end
+    reader.seek(SeekFrom::Start(5000)).unwrap();
+    assert_eq!(reader.read(&mut [0; 16]).unwrap(), 0);
+
+    let (archive, entry) = find("data/stored4.rar", "after.txt");
+    let mut data = String::new();
+    let mut reader = archive.open_seekable(&entry).unwrap().unwrap();
+    reader.read_to_string(&mut data).unwrap();
+    assert_eq!(data, "after\n");
+}
+
+#[test]
+fn compressed() {
+    let (archive, entry) = find("data/version.rar", "VERSION");
+    let err = archive.open_seekable(&entry).unwrap_err();
+    assert_eq!((err.code, err.when), (Code::NotStored, When::Process));
+    assert_eq!(err.filename().unwrap().to_str(), Some("VERSION"));
+}
+
+#[test]
+fn missing_entry() {
+    let (_, entry) = find("data/stored4.rar", "movie.bin");
+    let archive = Archive::new("data/volumes.part1.rar")
+        .open_for_listing()
+        .unwrap();
+    assert!(archive.open_seekable(&entry).unwrap().is_none());
+}
+
+#[test]
+fn missing_volume() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("stored4.rar");
+    std::fs::copy("data/stored4.rar", &path).unwrap();
+    let (_, entry) = find("data/stored4.rar", "movie.bin");
+    let archive = Archive::new(&path).open_for_listing().unwrap();
+    let err = archive.open_seekable(&entry).unwrap_err();
+    assert_eq!((err.code, err.when), (Code::EOpen, When::Process));
+    assert_eq!(err.volume(), Some(&*dir.path().join("stored4.r00")));
+}
+
+#[test]
+fn data_size_leading_back() {
+    // the data size of the header after the entry wraps the position around to the header
+    for path in ["data/header-loop.rar", "data/header-loop4.rar"] {
+        let (archive, entry) = find(path, "first.txt");
+        let mut data = String::new();
+        let mut reader = archive.open_seekable(&entry).unwrap().unwrap();
+        reader.read_to_string(&mut data).unwrap();
+        assert_eq!(data, "first\n");
+    }
+}
+
+#[test]
+fn same_name() {
+    let mut archive = Archive::new("data/duplicates.rar")
+        .open_for_listing()
+        .unwrap();
+    let older = archive.next().unwrap().unwrap();
+    let newer = archive.next().unwrap().unwrap();
+    assert_eq!(older.filename, newer.filename);
+    for (entry, expected) in [(newer, "newer data\n"), (older, "older\n")] {
+        let mut data = String::new();
+        let mut reader = archive.open_seekable(&entry).unwrap().unwrap();
+        reader.read_to_string(&mut data).unwrap();
+        assert_eq!(data, expected);
+    }
+}
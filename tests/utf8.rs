@@ -66,3 +66,55 @@ fn unicode_entry_extract() {
     assert_eq!(entries.len(), 1);
     assert_eq!(&entries[0].file_name(), "unicodefilename❤️.txt");
 }
+
+/// extracts all entries of `file` below `base`
+fn extract_all(file: &str, base: &std::path::Path) {
+    let mut archive = Archive::new(file).open_for_processing().unwrap();
+    while let Some(header) = archive.read_header().unwrap() {
+        archive = header.extract_with_base(base).expect("extract");
+    }
+}
+
+#[test]
+fn unicode_dirs_extract() {
+    let parent = tempfile::tempdir().unwrap();
+    extract_all("data/unicode-dirs.rar", parent.path());
+    let dir = parent.path().join("dïr❤️");
+    assert!(dir.is_dir());
+    assert!(dir.join("fïle✌.txt").is_file());
+    assert!(dir.join("sub/plain.txt").is_file());
+}
+
+#[test]
+fn unicode_dirs_extract_to_unicode_base() {
+    let parent = tempfile::tempdir().unwrap();
+    let base = parent.path().join("unicodefilename❤️");
+    extract_all("data/unicode-dirs.rar", &base);
+    extract_all("data/unicode.rar", &base);
+    assert!(base.join("dïr❤️/fïle✌.txt").is_file());
+    assert!(base.join("dïr❤️/sub/plain.txt").is_file());
+    assert!(base.join("te…―st✌").is_file());
+}
+
+#[test]
+#[cfg(unix)]
+fn extract_with_non_utf8_base() {
+    use std::os::unix::ffi::OsStrExt;
+    let parent = tempfile::tempdir().unwrap();
+    let base = parent.path().join(std::ffi::OsStr::from_bytes(b"lat\xe9n"));
+    // creating such a directory may fail on file systems that insist on UTF-8 names
+    if std::fs::create_dir(&base).is_err() {
+        return;
+    }
+    extract_all("data/unicode-dirs.rar", &base);
+    extract_all("data/unicode-entry.rar", &base);
+    extract_all("data/version.rar", &base);
+    assert!(base.join("dïr❤️/fïle✌.txt").is_file());
+    assert!(base.join("dïr❤️/sub/plain.txt").is_file());
+    assert!(base.join("unicodefilename❤️.txt").is_file());
+    assert_eq!(
+        std::fs::read_to_string(base.join("VERSION")).unwrap(),
+        "unrar-0.4.0"
+    );
+    assert_eq!(std::fs::read_dir(parent.path()).unwrap().count(), 1);
+}
@@ -57,7 +57,10 @@ fn main() {
         "dll",
         "qopen",
     ].iter().map(|&s| format!("vendor/unrar/{s}.cpp")).collect();
-    cc::Build::new()
+    println!("cargo:rerun-if-changed=src/errhnd.cpp");
+    let mut build = cc::Build::new();
+    sanitize(&mut build);
+    build
         .cpp(true) // Switch to C++ library compilation.
         .opt_level(2)
         .std("c++14")
@@ -82,6 +85,43 @@ fn main() {
         .define("_LARGEFILE_SOURCE", None)
         .define("RAR_SMP", None)
         .define("RARDLL", None)
+        // one ErrHandler per thread instead of a global one, see src/errhnd.cpp
+        .define("ErrHandler", Some("(*unrar_sys_err_handler())"))
         .files(&files)
+        .file("src/errhnd.cpp")
         .compile("libunrar.a");
 }
+
+/// Instruments the C++ sources when fuzzing or sanitizing the Rust side, so memory errors in
+/// the unpackers are caught too.
+///
+/// `-Zsanitizer=address` (which cargo-fuzz uses by default) is picked up automatically.
+/// Additional sanitizers can be requested with a comma separated list in `UNRAR_SYS_SANITIZE`,
+/// e.g. `UNRAR_SYS_SANITIZE=address,undefined`. UBSan is compiled in trap mode since rustc
+/// does not link its runtime.
+fn sanitize(build: &mut cc::Build) {
+    println!("cargo:rerun-if-env-changed=UNRAR_SYS_SANITIZE");
+    let rustc = std::env::var("CARGO_CFG_SANITIZE").unwrap_or_default();
+    let extra = std::env::var("UNRAR_SYS_SANITIZE").unwrap_or_default();
+    let mut sanitizers: Vec<&str> = rustc.split(',').chain(extra.split(',')).collect();
+    sanitizers.retain(|s| !s.is_empty());
+    sanitizers.sort_unstable();
+    sanitizers.dedup();
+    for sanitizer in &sanitizers {
+        build.flag(&format!("-fsanitize={sanitizer}"));
+        if *sanitizer == "undefined" {
+            build.flag_if_supported("-fsanitize-trap=undefined");
+            build.flag_if_supported("-fsanitize-undefined-trap-on-error");
+            // unrar reads integers from unaligned buffers on purpose (see ALLOW_MISALIGNED) and
+            // stores header types read from the archive in its HEADER_TYPE enum unchecked
+            build.flag("-fno-sanitize=alignment,enum");
+        }
+    }
+    if !sanitizers.is_empty() {
+        build.flag("-fno-omit-frame-pointer");
+    }
+    // `cargo fuzz` builds with `--cfg fuzzing`, let libFuzzer see the coverage of the C++ code
+    if std::env::var_os("CARGO_CFG_FUZZING").is_some() {
+        build.flag_if_supported("-fsanitize=fuzzer-no-link");
+    }
+}
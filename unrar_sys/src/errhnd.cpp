@@ -0,0 +1,12 @@
+// unrar keeps the state of errors in the global ErrHandler, which RAROpenArchiveEx resets
+// and then reads to tell why an archive could not be opened. build.rs replaces every use
+// of ErrHandler by this function, so each thread has a handler of its own and handles used
+// on different threads at once do not share the state of their errors.
+
+#include "../vendor/unrar/rar.hpp"
+
+ErrorHandler *unrar_sys_err_handler()
+{
+  static thread_local ErrorHandler Handler;
+  return &Handler;
+}
@@ -117,7 +117,10 @@ pub struct Handle { _private: [u8; 0] }
 
 // ----------------- STRUCTS ----------------- //
 
-#[repr(C)]
+// dll.hpp declares its structs within `#pragma pack(1)`, where all their fields are 4 byte
+// aligned, which `packed(4)` matches. `OpenArchiveData` lines up without it.
+
+#[repr(C, packed(4))]
 pub struct HeaderData {
     pub archive_name: [c_char; 260],
     pub filename: [c_char; 260],
@@ -136,7 +139,7 @@ pub struct HeaderData {
     pub comment_state: c_uint,
 }
 
-#[repr(C)]
+#[repr(C, packed(4))]
 pub struct HeaderDataEx {
     pub archive_name: [c_char; 1024],
     pub archive_name_w: [wchar_t; 1024],
@@ -184,7 +187,7 @@ pub struct OpenArchiveData {
     pub comment_state: c_uint,
 }
 
-#[repr(C)]
+#[repr(C, packed(4))]
 pub struct OpenArchiveDataEx {
     pub archive_name: *const c_char,
     pub archive_name_w: *const wchar_t,
@@ -344,6 +347,12 @@ impl OpenArchiveDataEx {
         Self::new_internal(std::ptr::null(), archive, mode)
     }
 
+    /// Names the archive by a wide string on every platform, which unrar prefers over the
+    /// narrow name.
+    pub fn new_wide(archive: *const wchar_t, mode: c_uint) -> Self {
+        Self::new_internal(std::ptr::null(), archive, mode)
+    }
+
     fn new_internal(
         archive_name: *const c_char,
         archive_name_w: *const wchar_t,
@@ -376,4 +385,20 @@ mod tests {
     fn test_version() {
         assert_eq!(unsafe { super::RARGetDllVersion() }, 9);
     }
+
+    #[test]
+    #[cfg(target_pointer_width = "64")]
+    fn test_layout() {
+        use std::mem::{offset_of, size_of};
+        // offsets of the packed structs in dll.hpp, where wchar_t has 2 bytes on Windows
+        // and 4 bytes elsewhere
+        let (redir_name, size) = match cfg!(windows) {
+            true => (6252, 10244),
+            false => (10348, 14340),
+        };
+        assert_eq!(offset_of!(super::HeaderDataEx, redir_name), redir_name);
+        assert_eq!(size_of::<super::HeaderDataEx>(), size);
+        assert_eq!(offset_of!(super::OpenArchiveDataEx, comment_buffer_w), 68);
+        assert_eq!(size_of::<super::OpenArchiveDataEx>(), 176);
+    }
 }